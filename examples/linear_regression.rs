@@ -1,5 +1,5 @@
 use newron::dataset::Dataset;
-use newron::layers::LayerEnum::*;
+use newron::layers::LayerEnum;
 use newron::sequential::Sequential;
 use newron::loss::{mse::MSE};
 use newron::metrics::Metric;
@@ -21,11 +21,11 @@ fn main() {
     let mut model = Sequential::new();
 
     // We only need one neuron (slope + intercept (bias))
-    model.add(Dense{input_units: 1, output_units:1});
+    model.add(LayerEnum::dense(Some(1), 1));
 
     model.compile(MSE{},
         SGD::new(0.0002),
-        vec![Metric::Accuracy]);
+        vec![Metric::MAE, Metric::RMSE, Metric::R2]).unwrap();
 
     model.summary();
 
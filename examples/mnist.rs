@@ -2,6 +2,7 @@ use std::path::Path;
 
 use newron::dataset::Dataset;
 use newron::layers::LayerEnum::*;
+use newron::initializers::Initializer;
 use newron::loss::{categorical_entropy::CategoricalEntropy};
 use newron::metrics::Metric;
 use newron::sequential::Sequential;
@@ -21,9 +22,15 @@ fn main() {
     let mut model = Sequential::new();
     model.set_seed(99);
 
+    model.set_input_shape(vec![dataset.get_number_features()]);
+
     model.add(Dense {
-        input_units: dataset.get_number_features(),
-        output_units: 256
+        input_units: None,
+        output_units: 256,
+        kernel_initializer: Initializer::HeUniform,
+        bias_initializer: Initializer::Zeros,
+        kernel_regularizer: None,
+        bias_regularizer: None,
     });
 
     model.add(Dropout {prob: 0.2});
@@ -31,13 +38,17 @@ fn main() {
     model.add(ReLU);
 
     model.add(Dense {
-        input_units: 256,
-        output_units: dataset.get_number_targets()
+        input_units: None,
+        output_units: dataset.get_number_targets(),
+        kernel_initializer: Initializer::GlorotUniform,
+        bias_initializer: Initializer::Zeros,
+        kernel_regularizer: None,
+        bias_regularizer: None,
     });
 
     model.compile(CategoricalEntropy{},
               SGD::new(0.2),
-              vec![Metric::Accuracy]);
+              vec![Metric::Accuracy]).unwrap();
 
     model.summary();
 
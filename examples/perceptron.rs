@@ -1,5 +1,5 @@
 use newron::dataset::Dataset;
-use newron::layers::LayerEnum::*;
+use newron::layers::LayerEnum::{self, *};
 use newron::sequential::Sequential;
 use newron::loss::{mse::MSE};
 use newron::metrics::Metric;
@@ -20,14 +20,14 @@ fn main() {
     
     model.set_seed(42);
 
-    model.add(Dense{input_units:3, output_units:4});
+    model.add(LayerEnum::dense(Some(3), 4));
     model.add(TanH);
 
-    model.add(Dense{input_units:4, output_units:1});
+    model.add(LayerEnum::dense(None, 1));
 
     model.compile(MSE{},
         SGD::new(0.02),
-        vec![Metric::Accuracy]);
+        vec![Metric::Accuracy]).unwrap();
 
     model.summary();
 
@@ -2,6 +2,7 @@ use std::path::Path;
 
 use newron::dataset::Dataset;
 use newron::layers::LayerEnum::*;
+use newron::initializers::Initializer;
 use newron::optimizers::sgd::SGD;
 use newron::sequential::Sequential;
 use newron::loss::{mse::MSE};
@@ -18,20 +19,28 @@ fn main() {
     model.set_seed(42);
 
     model.add(Dense {
-        input_units: dataset.get_number_features(),
-        output_units: 100
+        input_units: Some(dataset.get_number_features()),
+        output_units: 100,
+        kernel_initializer: Initializer::HeUniform,
+        bias_initializer: Initializer::Zeros,
+        kernel_regularizer: None,
+        bias_regularizer: None,
     });
     
     model.add(ReLU);
 
     model.add(Dense {
-        input_units: 100,
-        output_units: dataset.get_number_targets()
+        input_units: None,
+        output_units: dataset.get_number_targets(),
+        kernel_initializer: Initializer::GlorotUniform,
+        bias_initializer: Initializer::Zeros,
+        kernel_regularizer: None,
+        bias_regularizer: None,
     });
 
     model.compile(MSE{},
         SGD::new(0.0002),
-        vec![Metric::Accuracy]);
+        vec![Metric::MAE, Metric::RMSE, Metric::R2]).unwrap();
 
     model.fit(&dataset, 200, true);
 }
@@ -0,0 +1,58 @@
+/// Constraints applied to the parameters of layers after each optimizer step
+use crate::tensor::Tensor;
+
+/// Projection of a parameter on a set of allowed values. Norm constraints
+/// rescale each vector along `axis` separately: with `axis` 0, the columns
+/// of the [input_units, output_units] weights of a `Dense` layer, i.e. the
+/// incoming weights of each unit.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Constraint {
+    /// Rescale the vectors whose norm is greater than `max_value`
+    MaxNorm { max_value: f64, axis: usize },
+    /// Rescale every vector to a norm of 1
+    UnitNorm { axis: usize },
+    /// Move the norm of every vector towards [min_value, max_value]: `rate`
+    /// is 1 to clip the norm, lower to only go part of the way at each step
+    MinMaxNorm { min_value: f64, max_value: f64, rate: f64, axis: usize },
+    /// Replace negative values by zero
+    NonNeg,
+}
+
+impl Constraint {
+    pub fn apply(&self, tensor: &mut Tensor) {
+        match *self {
+            Constraint::MaxNorm { max_value, axis } => {
+                rescale(tensor, axis, |norm| norm.min(max_value));
+            }
+            Constraint::UnitNorm { axis } => {
+                rescale(tensor, axis, |_| 1.0);
+            }
+            Constraint::MinMaxNorm { min_value, max_value, rate, axis } => {
+                rescale(tensor, axis, |norm| rate * norm.clamp(min_value, max_value) + (1.0 - rate) * norm);
+            }
+            Constraint::NonNeg => {
+                tensor.data.iter_mut().for_each(|x| *x = x.max(0.0));
+            }
+        }
+    }
+}
+
+// Give each vector along `axis` the norm returned by `target` for its
+// current norm. Null vectors have no direction and are left unchanged.
+fn rescale(tensor: &mut Tensor, axis: usize, target: impl Fn(f64) -> f64) {
+    assert!(axis < tensor.shape.len(), "Cannot constrain the axis {} of a tensor of shape {:?}.", axis, tensor.shape);
+    let length = tensor.shape[axis];
+    let inner: usize = tensor.shape[axis + 1..].iter().product();
+    let outer: usize = tensor.shape[..axis].iter().product();
+
+    for o in 0..outer {
+        for i in 0..inner {
+            let indices: Vec<usize> = (0..length).map(|k| (o * length + k) * inner + i).collect();
+            let norm = indices.iter().map(|j| tensor.data[*j] * tensor.data[*j]).sum::<f64>().sqrt();
+            if norm > 0.0 {
+                let scale = target(norm) / norm;
+                indices.iter().for_each(|j| tensor.data[*j] *= scale);
+            }
+        }
+    }
+}
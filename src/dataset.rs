@@ -6,7 +6,20 @@ use std::io::{Read, BufReader, BufRead};
 use std::str::FromStr;
 
 use crate::tensor::Tensor;
-use crate::{random::Rand, utils};
+use crate::{random::{Rand, SeedSequence, SPLIT_STREAM}, utils};
+
+// Maximum number of rows processed at once by the models
+pub(crate) const BATCH_SIZE: usize = 128;
+
+// Split the row indexes `0..rows` in batches of at most `BATCH_SIZE` rows,
+// shuffled first with the random stream `shuffle` if one is given
+pub(crate) fn batch_indexes(rows: usize, shuffle: Option<u32>) -> Vec<Vec<usize>> {
+    let mut indexes: Vec<usize> = (0..rows).collect();
+    if let Some(seed) = shuffle {
+        Rand::new(seed).shuffle(&mut indexes[..]);
+    }
+    indexes.chunks(BATCH_SIZE).map(|batch| batch.to_vec()).collect()
+}
 
 #[derive(PartialEq, Debug)]
 pub enum ColumnType {
@@ -45,7 +58,8 @@ pub enum DatasetError {
 pub struct Dataset {
     // Contains all data for dataset
     data: Vec<Row>,
-    columns_metadata: Vec<ColumnMetadata>
+    columns_metadata: Vec<ColumnMetadata>,
+    seed: u32
 }
 
 impl Dataset {
@@ -80,6 +94,7 @@ impl Dataset {
         Ok(Dataset {
             data: rows,
             columns_metadata,
+            seed: 0,
         })
     }
 
@@ -252,27 +267,43 @@ impl Dataset {
     /// Get the tensor from the dataset with the `row_type` and 
     /// `column_type` specified.
     pub fn get_tensor(&self, row_type: RowType, col_type: ColumnType) -> Tensor {
-        let rows = self.count_row_type(&row_type);
-        let cols = self.count_column_type(&col_type);
-        let shape = vec![rows, cols];
+        self.get_rows_tensor(&self.row_indexes(&row_type), &col_type)
+    }
+
+    /// Split the rows with the `row_type` specified in batches of at most
+    /// `batch_size` rows and iterate over the (features, targets) tensors
+    /// of each batch. The rows of this type are looked up once for all the
+    /// batches.
+    pub fn batches(&self, row_type: RowType, batch_size: usize) -> impl Iterator<Item = (Tensor, Tensor)> + '_ {
+        let indexes = self.row_indexes(&row_type);
+        (0..indexes.len()).step_by(batch_size).map(move |start| {
+            let batch = &indexes[start..cmp::min(start + batch_size, indexes.len())];
+            (self.get_rows_tensor(batch, &ColumnType::Feature), self.get_rows_tensor(batch, &ColumnType::Target))
+        })
+    }
 
+    // Indexes in `data` of the rows matching the type `row_type`
+    fn row_indexes(&self, row_type: &RowType) -> Vec<usize> {
+        self.data.iter().enumerate().filter(|(_, r)| r.row_type == *row_type).map(|(i, _)| i).collect()
+    }
+
+    // Tensor of the rows at `indexes` keeping the columns of type `col_type`
+    fn get_rows_tensor(&self, indexes: &[usize], col_type: &ColumnType) -> Tensor {
         let mut col_indexes = Vec::new();
         for (i, col) in self.columns_metadata.iter().enumerate() {
-            if col.column_type == col_type {
+            if col.column_type == *col_type {
                 col_indexes.push(i);
             }
         }
 
-        let mut result = Vec::new();
-        for row in &self.data {
-            if row.row_type == row_type {
-                for col in &col_indexes {
-                    result.push(row.data[*col]);
-                }
+        let mut result = Vec::with_capacity(indexes.len() * col_indexes.len());
+        for index in indexes {
+            for col in &col_indexes {
+                result.push(self.data[*index].data[*col]);
             }
         }
 
-        Tensor::new(result, shape)
+        Tensor::new(result, vec![indexes.len(), col_indexes.len()])
     }
 
     // Count the number of columns in the dataset matching the type `col_type`
@@ -296,14 +327,19 @@ impl Dataset {
         self.data.len()
     }
 
+    /// Seed the random number generator used by `split_train_test`
+    pub fn set_seed(&mut self, s: u32) {
+        self.seed = s;
+    }
+
     /// Set `percentage` of rows to be `RowType::Train`. Remaining rows are set
     /// to `RowType::Test`. If `shuffle` is true, rows are set randomly.
     pub fn split_train_test(&mut self, percentage: f64, shuffle: bool) {
         let mut index = (0..self.data.len()).collect::<Vec<usize>>();
         
         if shuffle {
-            let mut rand = Rand::new(18);
-            rand.shuffle(&mut index[..]);
+            let seed = SeedSequence::new(self.seed).child(SPLIT_STREAM).seed();
+            Rand::new(seed).shuffle(&mut index[..]);
         }
 
         let stop_index = (percentage * index.len() as f64) as usize;
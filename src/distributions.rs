@@ -0,0 +1,153 @@
+//! Tensors filled with random values of a distribution. Every function takes
+//! the seed of the crate's random number generator so results are reproducible.
+
+use crate::random::Rand;
+use crate::tensor::Tensor;
+
+impl Tensor {
+    /// Creates a Tensor filled with uniformly distributed random values
+    /// between -1 and +1 with the `shape` specified.
+    pub fn random(shape: Vec<usize>, seed: u32) -> Tensor {
+        Tensor::random_uniform(shape, -1.0, 1.0, seed)
+    }
+
+    /// Creates a Tensor filled with random values uniformly distributed
+    /// between `low` (included) and `high` (excluded).
+    pub fn random_uniform(shape: Vec<usize>, low: f64, high: f64, seed: u32) -> Tensor {
+        assert!(low <= high, "Uniform distribution with low {} greater than high {}.", low, high);
+        let mut rng = Rand::new(seed);
+
+        let number_values = shape.iter().product();
+        let data = (0..number_values).map(|_| low + (high - low) * rng.rand_float()).collect();
+        Tensor { data, shape }
+    }
+
+    /// Generates a Tensor filled with random values following a normal distribution
+    /// with parameters mu and sigma specified (mean/stdev)
+    pub fn random_normal(shape: Vec<usize>, mean: f64, stdev: f64, seed: u32) -> Tensor {
+        let mut rng = Rand::new(seed);
+
+        let number_values = shape.iter().product();
+        let data = (0..number_values).map(|_| rng.standard_normal() * stdev + mean).collect();
+        Tensor { data, shape }
+    }
+
+    /// Normal distribution whose values further than 2 stdev from the mean
+    /// are drawn again.
+    pub fn truncated_normal(shape: Vec<usize>, mean: f64, stdev: f64, seed: u32) -> Tensor {
+        let mut rng = Rand::new(seed);
+
+        let number_values = shape.iter().product();
+        let data = (0..number_values).map(|_| loop {
+            let value = rng.standard_normal();
+            if value.abs() <= 2.0 {
+                break value * stdev + mean;
+            }
+        }).collect();
+        Tensor { data, shape }
+    }
+
+    /// Creates a Tensor whose values are 1 with probability `prob` and 0 otherwise.
+    pub fn bernoulli(shape: Vec<usize>, prob: f64, seed: u32) -> Tensor {
+        assert!((0.0..=1.0).contains(&prob), "Bernoulli probability {} is not between 0 and 1.", prob);
+        let mut rng = Rand::new(seed);
+
+        let number_values = shape.iter().product();
+        let data = (0..number_values).map(|_| if rng.rand_float() < prob { 1.0 } else { 0.0 }).collect();
+        Tensor { data, shape }
+    }
+
+    /// Inverted dropout mask: values are 0 with probability `prob` and
+    /// 1 / (1 - prob) otherwise, so the mask keeps the mean of its inputs.
+    pub fn mask(shape: &[usize], prob: f64, seed: u32) -> Tensor {
+        assert!(prob < 1.0, "Cannot drop every value (prob {}).", prob);
+        let keep = Tensor::bernoulli(shape.to_vec(), 1.0 - prob, seed);
+        (1.0 / (1.0 - prob)) * keep
+    }
+
+    /// Draws `samples` classes (with replacement) for each row of `probs`
+    /// whose last axis gives the probability of every class. Probabilities
+    /// are normalized so they only need to be positive.
+    /// The class indices are returned in a Tensor whose last axis has
+    /// `samples` values instead of the number of classes.
+    pub fn categorical(probs: &Tensor, samples: usize, seed: u32) -> Tensor {
+        let mut rng = Rand::new(seed);
+        let classes = Tensor::check_probabilities(probs);
+
+        let mut data = Vec::with_capacity(probs.data.len() / classes * samples);
+        for row in probs.data.chunks(classes) {
+            for _ in 0..samples {
+                data.push(Tensor::sample_class(&mut rng, row) as f64);
+            }
+        }
+
+        let mut shape = probs.shape.to_vec();
+        *shape.last_mut().unwrap() = samples;
+        Tensor { data, shape }
+    }
+
+    /// Counts how many times each class is drawn out of `trials` draws for
+    /// each row of `probs` (see `categorical`). The result has the shape
+    /// of `probs` and each row sums to `trials`.
+    pub fn multinomial(probs: &Tensor, trials: usize, seed: u32) -> Tensor {
+        let mut rng = Rand::new(seed);
+        let classes = Tensor::check_probabilities(probs);
+
+        let mut data = vec![0.0; probs.data.len()];
+        for (row, counts) in probs.data.chunks(classes).zip(data.chunks_mut(classes)) {
+            for _ in 0..trials {
+                counts[Tensor::sample_class(&mut rng, row)] += 1.0;
+            }
+        }
+        Tensor { data, shape: probs.shape.to_vec() }
+    }
+
+    /// Creates a 1D Tensor containing the indices 0 to `n` - 1 in random order.
+    pub fn permutation(n: usize, seed: u32) -> Tensor {
+        Tensor::choice(n, n, false, seed)
+    }
+
+    /// Draws `count` indices between 0 and `n` - 1 (1D Tensor). Without
+    /// `replace`, an index is drawn at most once (`count` <= `n`).
+    pub fn choice(n: usize, count: usize, replace: bool, seed: u32) -> Tensor {
+        let mut rng = Rand::new(seed);
+
+        let data = if replace {
+            assert!(n > 0 || count == 0, "Cannot choose among 0 values.");
+            (0..count).map(|_| rng.below(n as u64) as f64).collect()
+        } else {
+            assert!(count <= n, "Cannot choose {} distinct values among {}.", count, n);
+            // the first `count` steps of a Fisher-Yates shuffle
+            let mut indices: Vec<usize> = (0..n).collect();
+            for i in 0..count {
+                let j = i + rng.below((n - i) as u64) as usize;
+                indices.swap(i, j);
+            }
+            indices[..count].iter().map(|i| *i as f64).collect()
+        };
+        Tensor { data, shape: vec![count] }
+    }
+
+    // Return the number of classes (last axis) of valid probabilities
+    fn check_probabilities(probs: &Tensor) -> usize {
+        let classes = *probs.shape.last().expect("Probabilities must have at least one axis.");
+        assert!(classes > 0, "Probabilities must have at least one class.");
+        assert!(probs.data.iter().all(|p| *p >= 0.0), "Probabilities must be positive.");
+        assert!(probs.data.chunks(classes).all(|row| row.iter().sum::<f64>() > 0.0),
+            "Probabilities of a row must not all be zero.");
+        classes
+    }
+
+    // Inverse of the cumulative distribution of the (unnormalized) `probs`
+    fn sample_class(rng: &mut Rand, probs: &[f64]) -> usize {
+        let mut threshold = rng.rand_float() * probs.iter().sum::<f64>();
+        for (class, p) in probs.iter().enumerate() {
+            if threshold < *p {
+                return class;
+            }
+            threshold -= p;
+        }
+        // rounding errors: last class with a non-zero probability
+        probs.iter().rposition(|p| *p > 0.0).unwrap()
+    }
+}
@@ -0,0 +1,623 @@
+/// The Graph model connects layers as a directed acyclic graph: a node
+/// can take the output of any previous node, several nodes can be merged and
+/// the model can have several inputs and outputs.
+use std::cmp;
+use std::fmt;
+
+use crate::layers::layer::{Layer, LearnableParams};
+use crate::constraints::Constraint;
+use crate::layers::LayerEnum;
+use crate::layers::merge::{Merge, MergeOp};
+use crate::metrics::{Evaluation, Metric, MetricError, MetricValue, streaming_metric::StreamingMetric};
+use crate::sequential::{Sequential, ModelError};
+use crate::tensor::Tensor;
+use crate::dataset::{self, Dataset, RowType, ColumnType};
+use crate::random::{SeedSequence, LAYER_STREAMS, SHUFFLE_STREAMS};
+use crate::{loss::loss::Loss, optimizers::optimizer::OptimizerStep, optimizers::sgd::SGD};
+use crate::utils;
+
+// Node as described by the user
+enum NodeKind {
+    Input(Vec<usize>),
+    Layer(LayerEnum),
+    Merge(MergeOp),
+}
+
+struct Node {
+    name: String,
+    kind: NodeKind,
+    inputs: Vec<String>,
+}
+
+// Node created by `compile`
+enum Operation {
+    Input,
+    // index of the layer in `Graph::layers`
+    Layer(usize),
+    Merge(Merge),
+}
+
+struct CompiledNode {
+    operation: Operation,
+    // indices of the input nodes
+    inputs: Vec<usize>,
+    output_shape: Vec<usize>,
+}
+
+struct Output {
+    name: String,
+    loss: Box<dyn Loss>,
+    weight: f64,
+    metrics: Vec<Box<dyn StreamingMetric>>,
+}
+
+pub struct Graph {
+    nodes: Vec<Node>,
+    outputs: Vec<Output>,
+    compiled: Vec<CompiledNode>,
+    // indices of the output nodes (same order as `outputs`)
+    output_nodes: Vec<usize>,
+    pub layers: Vec<Box<dyn Layer>>,
+    // (node name, parameter, constraint) added by the user
+    constraints: Vec<(String, LearnableParams, Constraint)>,
+    // (index in `layers`, parameter, constraint) applied after each optimizer step
+    layer_constraints: Vec<(usize, LearnableParams, Constraint)>,
+    // names of the layer nodes frozen by `set_trainable`
+    frozen: Vec<String>,
+    optim: Box<dyn OptimizerStep>,
+    seed: u32,
+    // shuffle the training rows at each epoch of `fit`
+    shuffle: bool,
+    // epochs trained by all the calls to `fit`, each one is shuffled differently
+    trained_epochs: u64,
+}
+
+/// Error returned by `Graph::compile` when the nodes do not fit together
+#[derive(Debug, PartialEq)]
+pub enum GraphError {
+    /// Several nodes have the same name
+    DuplicateNode { node: String },
+    /// The node `node` takes the output of `input` which is not added before it
+    UnknownNode { node: String, input: String },
+    /// `add_output` refers to a node that does not exist
+    UnknownOutput { node: String },
+    /// The model needs at least one input and one output
+    MissingInputOrOutput,
+    /// The node does not accept the output shapes of its inputs
+    IncompatibleShape { node: String, node_type: String, input_shapes: Vec<Vec<usize>>, message: String },
+    /// A constraint is added to a node which is not a layer having the parameter
+    UnknownParameter { node: String, param: LearnableParams },
+    /// `set_trainable` is called for a node which is not a layer supporting it
+    NotFreezable { node: String },
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphError::DuplicateNode { node } => {
+                write!(f, "Node {}: a node with this name already exists.", node)
+            }
+            GraphError::UnknownNode { node, input } => {
+                write!(f, "Node {}: unknown input node {} (inputs must be added before).", node, input)
+            }
+            GraphError::UnknownOutput { node } => {
+                write!(f, "Output {}: unknown node.", node)
+            }
+            GraphError::MissingInputOrOutput => {
+                write!(f, "The model needs at least one input and one output.")
+            }
+            GraphError::IncompatibleShape { node, node_type, input_shapes, message } => {
+                write!(f, "Node {} ({}) with input shapes {:?}: {}.", node, node_type, input_shapes, message)
+            }
+            GraphError::UnknownParameter { node, param } => {
+                write!(f, "Node {}: no {:?} parameters to constrain.", node, param)
+            }
+            GraphError::NotFreezable { node } => {
+                write!(f, "Node {}: not a layer which can be frozen.", node)
+            }
+        }
+    }
+}
+
+impl Graph {
+    /// Create a new empty Graph model.
+    pub fn new() -> Graph {
+        Graph {
+            nodes: vec![],
+            outputs: vec![],
+            compiled: vec![],
+            output_nodes: vec![],
+            layers: vec![],
+            constraints: vec![],
+            layer_constraints: vec![],
+            frozen: vec![],
+            optim: Box::new(SGD::new(0.02)),
+            seed: 0,
+            shuffle: false,
+            trained_epochs: 0,
+        }
+    }
+
+    /// Seed the random number generator
+    pub fn set_seed(&mut self, s: u32) {
+        self.seed = s;
+    }
+
+    /// Shuffle the training rows at each epoch of `fit`
+    pub fn set_shuffle(&mut self, shuffle: bool) {
+        self.shuffle = shuffle;
+    }
+
+    /// Add an input node receiving samples of shape `shape` (without the
+    /// batch dimension). Inputs are fed in the order they are added.
+    pub fn add_input(&mut self, name: &str, shape: Vec<usize>) {
+        self.add_node(name, NodeKind::Input(shape), vec![]);
+    }
+
+    /// Add a layer taking the output of the node `input`
+    pub fn add_layer(&mut self, name: &str, layer: LayerEnum, input: &str) {
+        self.add_node(name, NodeKind::Layer(layer), vec![input.to_string()]);
+    }
+
+    /// Add a node combining the outputs of the nodes `inputs` with `op`
+    pub fn add_merge(&mut self, name: &str, op: MergeOp, inputs: &[&str]) {
+        self.add_node(name, NodeKind::Merge(op), inputs.iter().map(|input| input.to_string()).collect());
+    }
+
+    /// Constrain the parameter `param` of the layer node `node` after each
+    /// optimizer step
+    pub fn add_constraint(&mut self, node: &str, param: LearnableParams, constraint: Constraint) {
+        self.constraints.push((node.to_string(), param, constraint));
+    }
+
+    /// Freeze (`false`) or unfreeze the layer node `node`: optimizers do
+    /// not update the parameters of frozen layers. The flag is kept when
+    /// the model is compiled again.
+    pub fn set_trainable(&mut self, node: &str, trainable: bool) {
+        self.frozen.retain(|name| name != node);
+        if !trainable {
+            self.frozen.push(node.to_string());
+        }
+        // the layer is already built
+        if let Some(layer) = self.layer_index(node) {
+            self.layers[layer].set_trainable(trainable);
+        }
+    }
+
+    // Index in `layers` of the layer node `node` once compiled
+    fn layer_index(&self, node: &str) -> Option<usize> {
+        let position = self.nodes.iter().position(|other| other.name == node)?;
+        match self.compiled.get(position)?.operation {
+            Operation::Layer(layer) => Some(layer),
+            _ => None,
+        }
+    }
+
+    /// Use the node `name` as an output of the model trained with `loss`.
+    /// The loss of the model is the sum of the output losses multiplied by
+    /// their `weight`. Targets are given in the order outputs are added.
+    /// The `metrics` of the output are computed by `evaluate`.
+    pub fn add_output<T: 'static + Loss>(&mut self, name: &str, loss: T, weight: f64, metrics: Vec<Metric>) {
+        let metrics = metrics.into_iter().map(Metric::into_streaming).collect();
+        self.outputs.push(Output { name: name.to_string(), loss: Box::new(loss), weight, metrics });
+    }
+
+    fn add_node(&mut self, name: &str, kind: NodeKind, inputs: Vec<String>) {
+        self.nodes.push(Node { name: name.to_string(), kind, inputs });
+    }
+
+    /// Get a summary of the model
+    pub fn summary(&self) {
+        println!("Graph model using {} nodes and {} layers.", self.compiled.len(), self.layers.len());
+        println!("
+___________________________________________________________________________________
+Node (type)                  Output Shape              Param #   Connected to
+===================================================================================");
+
+        let mut trainable_param_sum = 0;
+        let mut non_trainable_param_sum = 0;
+
+        for (node, compiled) in self.nodes.iter().zip(&self.compiled) {
+            let (node_type, trainable_param, non_trainable_param) = match &compiled.operation {
+                Operation::Input => ("Input".to_string(), 0, 0),
+                Operation::Layer(index) => {
+                    let layer_info = self.layers[*index].get_info();
+                    if self.layers[*index].is_trainable() {
+                        (layer_info.layer_type, layer_info.trainable_param, layer_info.non_trainable_param)
+                    } else {
+                        // parameters of frozen layers are not trained
+                        (layer_info.layer_type, 0, layer_info.trainable_param + layer_info.non_trainable_param)
+                    }
+                }
+                Operation::Merge(merge) => (format!("{:?}", merge.get_op()), 0, 0),
+            };
+            let node_type = utils::fit_string_to_length(format!("{} ({})", node.name, node_type), 29);
+            // the first dimension is the batch size
+            let output_shape_str = "(None".to_string() + &compiled.output_shape
+                        .iter()
+                        .map(|u| format!(", {}", u))
+                        .collect::<String>() + ")";
+            let output_shape = utils::fit_string_to_length(output_shape_str, 26);
+            let param = utils::fit_string_to_length((trainable_param + non_trainable_param).to_string(), 10);
+
+            println!("{}{}{}{}", node_type, output_shape, param, node.inputs.join(", "));
+
+            trainable_param_sum += trainable_param;
+            non_trainable_param_sum += non_trainable_param;
+        }
+        let total_params = trainable_param_sum + non_trainable_param_sum;
+        println!("===================================================================================");
+        println!("Total params: {}", total_params);
+        println!("Trainable params: {}", trainable_param_sum);
+        println!("Non trainable params: {}", non_trainable_param_sum);
+    }
+
+    /// Create the layers and check the shapes of every node
+    pub fn compile<U: 'static + OptimizerStep>(&mut self, optim: U) -> Result<(), GraphError> {
+        self.optim = Box::new(optim);
+        self.compiled.clear();
+        self.layers.clear();
+        // each layer has its own random stream
+        let seeds = SeedSequence::new(self.seed).child(LAYER_STREAMS);
+
+        for (index, node) in self.nodes.iter().enumerate() {
+            if self.nodes[..index].iter().any(|other| other.name == node.name) {
+                return Err(GraphError::DuplicateNode { node: node.name.to_string() });
+            }
+
+            // nodes only take the output of previous nodes so the graph has no cycle
+            let mut inputs = Vec::new();
+            for input in &node.inputs {
+                match self.nodes[..index].iter().position(|other| &other.name == input) {
+                    Some(position) => inputs.push(position),
+                    None => return Err(GraphError::UnknownNode { node: node.name.to_string(), input: input.to_string() }),
+                }
+            }
+            let input_shapes: Vec<Vec<usize>> = inputs.iter().map(|i| self.compiled[*i].output_shape.to_vec()).collect();
+            let incompatible = |node_type: String, message: String| GraphError::IncompatibleShape {
+                node: node.name.to_string(),
+                node_type,
+                input_shapes: input_shapes.to_vec(),
+                message,
+            };
+
+            let (operation, output_shape) = match &node.kind {
+                NodeKind::Input(shape) => (Operation::Input, shape.to_vec()),
+                NodeKind::Layer(layer) => {
+                    let shape = Some(input_shapes[0].to_vec());
+                    let mut built_layer = Sequential::create_layer(layer, index, &shape, seeds.child(index as u64).seed()).map_err(|error| match error {
+                        ModelError::IncompatibleShape { layer_type, message, .. } => incompatible(layer_type, message),
+                        ModelError::UnknownInputShape { .. } => unreachable!("the input shape of a node is always known"),
+                        ModelError::UnknownParameter { .. } | ModelError::NotFreezable { .. } => {
+                            unreachable!("constraints and frozen layers are not checked when creating layers")
+                        }
+                    })?;
+                    let output_shape = built_layer.build(&input_shapes[0])
+                        .map_err(|message| incompatible(built_layer.get_info().layer_type, message))?;
+                    self.layers.push(built_layer);
+                    (Operation::Layer(self.layers.len() - 1), output_shape)
+                }
+                NodeKind::Merge(op) => {
+                    let mut merge = Merge::new(*op);
+                    let output_shape = merge.build(&input_shapes).map_err(|message| incompatible(format!("{:?}", op), message))?;
+                    (Operation::Merge(merge), output_shape)
+                }
+            };
+            self.compiled.push(CompiledNode { operation, inputs, output_shape });
+        }
+
+        self.output_nodes.clear();
+        for output in &self.outputs {
+            match self.nodes.iter().position(|node| node.name == output.name) {
+                Some(position) => self.output_nodes.push(position),
+                None => return Err(GraphError::UnknownOutput { node: output.name.to_string() }),
+            }
+        }
+        if self.output_nodes.is_empty() || !self.compiled.iter().any(|node| matches!(node.operation, Operation::Input)) {
+            return Err(GraphError::MissingInputOrOutput);
+        }
+
+        self.layer_constraints.clear();
+        for name in &self.frozen {
+            let layer = self.layer_index(name).ok_or_else(|| GraphError::NotFreezable { node: name.to_string() })?;
+            let layer = &mut self.layers[layer];
+            layer.set_trainable(false);
+            if layer.is_trainable() && !layer.get_params_list().is_empty() {
+                return Err(GraphError::NotFreezable { node: name.to_string() });
+            }
+        }
+
+        for (name, param, constraint) in &self.constraints {
+            let layer = self.layer_index(name)
+                .filter(|layer| self.layers[*layer].get_params_list().contains(param));
+            match layer {
+                Some(layer) => self.layer_constraints.push((layer, param.clone(), *constraint)),
+                None => return Err(GraphError::UnknownParameter { node: name.to_string(), param: param.clone() }),
+            }
+        }
+
+        Ok(())
+    }
+
+    // Number of values per row of each input
+    fn input_sizes(&self) -> Vec<usize> {
+        self.compiled.iter()
+            .filter(|node| matches!(node.operation, Operation::Input))
+            .map(|node| node.output_shape.iter().product())
+            .collect()
+    }
+
+    // Number of values per row of each output
+    fn output_sizes(&self) -> Vec<usize> {
+        self.output_nodes.iter().map(|node| self.compiled[*node].output_shape.iter().product()).collect()
+    }
+
+    // Return the output of every node given the inputs
+    fn forward_propagation(&mut self, inputs: Vec<Tensor>, train: bool) -> Vec<Tensor> {
+        let mut inputs = inputs.into_iter();
+        let mut values: Vec<Tensor> = Vec::with_capacity(self.compiled.len());
+
+        for node in self.compiled.iter_mut() {
+            let value = match &mut node.operation {
+                Operation::Input => {
+                    // rows of a dataset are flat, give them the input shape
+                    let input = inputs.next().expect("Not enough inputs given to the model.");
+                    let mut shape = vec![input.shape[0]];
+                    shape.extend(&node.output_shape);
+                    Tensor::new(input.data, shape)
+                }
+                Operation::Layer(index) => self.layers[*index].forward(values[node.inputs[0]].clone(), train),
+                Operation::Merge(merge) => merge.forward(node.inputs.iter().map(|i| values[*i].clone()).collect()),
+            };
+            values.push(value);
+        }
+
+        values
+    }
+
+    // Propagate the gradients of the loss with respect to the outputs
+    fn backward_propagation(&mut self, values: &[Tensor], output_grads: Vec<Tensor>) {
+        let mut grads: Vec<Option<Tensor>> = vec![None; self.compiled.len()];
+        for (node, grad) in self.output_nodes.iter().zip(output_grads) {
+            grads[*node] = Some(match grads[*node].take() {
+                Some(sum) => sum + grad,
+                None => grad,
+            });
+        }
+
+        for index in (0..self.compiled.len()).rev() {
+            // nodes used by no output get a zero gradient so every layer has gradients
+            let grad = grads[index].take().unwrap_or_else(|| Tensor::zero(values[index].shape.to_vec()));
+            let node = &mut self.compiled[index];
+            let input_grads = match &mut node.operation {
+                Operation::Input => vec![],
+                Operation::Layer(layer) => vec![self.layers[*layer].backward(&grad)],
+                Operation::Merge(merge) => merge.backward(&grad),
+            };
+
+            // a node used by several nodes receives the sum of their gradients
+            for (input, input_grad) in node.inputs.iter().zip(input_grads) {
+                grads[*input] = Some(match grads[*input].take() {
+                    Some(sum) => sum + input_grad,
+                    None => input_grad,
+                });
+            }
+        }
+    }
+
+    // Split the columns of `tensor` in tensors of `sizes` columns
+    fn split_columns(tensor: &Tensor, sizes: &[usize]) -> Vec<Tensor> {
+        let rows = tensor.shape[0];
+        let mut start = 0;
+        sizes.iter().map(|size| {
+            let mut data = Vec::with_capacity(rows * size);
+            for row in tensor.data.chunks(tensor.shape[1]) {
+                data.extend_from_slice(&row[start..start + size]);
+            }
+            start += size;
+            Tensor::new(data, vec![rows, *size])
+        }).collect()
+    }
+
+    // Split the features between the inputs and the targets between the outputs
+    fn split_dataset(&self, dataset: &Dataset, row_type: RowType) -> (Vec<Tensor>, Vec<Tensor>) {
+        let (input_sizes, output_sizes) = (self.input_sizes(), self.output_sizes());
+        let (features, outputs) = (input_sizes.iter().sum::<usize>(), output_sizes.iter().sum::<usize>());
+        assert_eq!(features, dataset.get_number_features(),
+            "The model expects {} features per row but the dataset has {}.", features, dataset.get_number_features());
+        assert_eq!(outputs, dataset.get_number_targets(),
+            "The model outputs {} values per row but the dataset has {} targets.", outputs, dataset.get_number_targets());
+
+        let inputs = Graph::split_columns(&dataset.get_tensor(row_type, ColumnType::Feature), &input_sizes);
+        let targets = Graph::split_columns(&dataset.get_tensor(row_type, ColumnType::Target), &output_sizes);
+        (inputs, targets)
+    }
+
+    // Flatten the samples of each tensor to rows
+    fn to_rows(tensors: &[Tensor]) -> Vec<Tensor> {
+        tensors.iter().map(|tensor| {
+            let rows = tensor.shape[0];
+            Tensor::new(tensor.data.to_vec(), vec![rows, tensor.data.len() / cmp::max(rows, 1)])
+        }).collect()
+    }
+
+    fn check_sizes(&self, inputs: &[Tensor], targets: &[Tensor]) {
+        let input_sizes: Vec<usize> = Graph::to_rows(inputs).iter().map(|input| input.shape[1]).collect();
+        assert_eq!(input_sizes, self.input_sizes(), "Wrong number of values per row for the inputs of the model.");
+        let target_sizes: Vec<usize> = Graph::to_rows(targets).iter().map(|target| target.shape[1]).collect();
+        assert_eq!(target_sizes, self.output_sizes(), "Wrong number of values per row for the targets of the model.");
+    }
+
+    // Return the weighted loss, the loss and the prediction of each output
+    // on the batch
+    fn batch_losses(&mut self, inputs: Vec<Tensor>, targets: &[Tensor], train: bool) -> (f64, Vec<f64>, Vec<Tensor>) {
+        let values = self.forward_propagation(inputs, train);
+
+        let mut total = 0.0;
+        let mut losses = Vec::new();
+        let mut predictions = Vec::new();
+        let mut output_grads = Vec::new();
+        for ((output, node), target) in self.outputs.iter().zip(&self.output_nodes).zip(targets) {
+            let value = &values[*node];
+            let predicted = Tensor::new(value.data.to_vec(), target.shape.to_vec());
+            let loss = output.loss.compute_loss(target, &predicted);
+            total += output.weight * loss;
+            losses.push(loss);
+
+            if train {
+                let grad = output.weight * output.loss.compute_loss_grad(target, &predicted);
+                output_grads.push(Tensor::new(grad.data, value.shape.to_vec()));
+            }
+            predictions.push(predicted);
+        }
+
+        if train {
+            // penalties of the regularizers
+            total += self.layers.iter().map(|layer| layer.get_regularization_loss()).sum::<f64>();
+            self.backward_propagation(&values, output_grads);
+            self.optim.step(&mut self.layers);
+            // frozen layers keep their parameters unchanged
+            for (layer, param, constraint) in &self.layer_constraints {
+                let layer = &mut self.layers[*layer];
+                if layer.is_trainable() {
+                    constraint.apply(layer.get_param(param));
+                }
+            }
+        }
+        (total, losses, predictions)
+    }
+
+    /// Train the model on the dataset. The features columns are split
+    /// between the inputs and the targets columns between the outputs in
+    /// the order they were added.
+    pub fn fit(&mut self, dataset: &Dataset, epochs: u32, verbose: bool) {
+        let (inputs, targets) = self.split_dataset(dataset, RowType::Train);
+
+        for epoch in 0..epochs {
+            let train_loss = self.fit_epoch(&inputs, &targets);
+
+            if verbose {
+                println!("\n------\nEpoch: {}", epoch);
+                println!("Train loss: {:.4}", train_loss);
+
+                if dataset.count_row_type(&RowType::Test) > 0 {
+                    match self.evaluate(dataset, RowType::Test) {
+                        Ok(evaluation) => {
+                            println!("Test loss:  {:.4}", evaluation.loss);
+                            evaluation.print_metrics();
+                        }
+                        Err(error) => println!("{}", error),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Train the model with one tensor per input and one tensor per output,
+    /// the first dimension of every tensor being the samples.
+    pub fn fit_tensors(&mut self, inputs: &[Tensor], targets: &[Tensor], epochs: u32, verbose: bool) {
+        self.check_sizes(inputs, targets);
+        let (inputs, targets) = (Graph::to_rows(inputs), Graph::to_rows(targets));
+
+        for epoch in 0..epochs {
+            let train_loss = self.fit_epoch(&inputs, &targets);
+
+            if verbose {
+                println!("\n------\nEpoch: {}", epoch);
+                println!("Train loss: {:.4}", train_loss);
+            }
+        }
+    }
+
+    // Train the model on every row once and return the mean loss of the batches
+    fn fit_epoch(&mut self, inputs: &[Tensor], targets: &[Tensor]) -> f64 {
+        // each epoch of every call to `fit` is shuffled differently
+        let shuffle = self.shuffle.then(|| SeedSequence::new(self.seed).child(SHUFFLE_STREAMS).child(self.trained_epochs).seed());
+        let batches = dataset::batch_indexes(inputs[0].shape[0], shuffle);
+        self.trained_epochs += 1;
+
+        let mut epoch_loss = 0.0;
+        for batch in &batches {
+            let batch_inputs = inputs.iter().map(|input| input.get_rows(batch)).collect();
+            let batch_targets: Vec<Tensor> = targets.iter().map(|target| target.get_rows(batch)).collect();
+
+            epoch_loss += self.batch_losses(batch_inputs, &batch_targets, true).0;
+        }
+        epoch_loss / batches.len() as f64
+    }
+
+    /// Evaluate the model on the rows of `dataset` having the `row_type`
+    /// specified. Return the weighted loss, the loss of each output (named
+    /// "<output> loss") and the value of its metrics (named "<output>
+    /// <metric>"), or an error if a metric cannot be computed on the
+    /// targets of its output.
+    pub fn evaluate(&mut self, dataset: &Dataset, row_type: RowType) -> Result<Evaluation, MetricError> {
+        let (inputs, targets) = self.split_dataset(dataset, row_type);
+        self.evaluate_tensors(&inputs, &targets)
+    }
+
+    /// Same as `evaluate` with one tensor per input and one tensor per output
+    pub fn evaluate_tensors(&mut self, inputs: &[Tensor], targets: &[Tensor]) -> Result<Evaluation, MetricError> {
+        self.check_sizes(inputs, targets);
+        let (inputs, targets) = (Graph::to_rows(inputs), Graph::to_rows(targets));
+        let rows = inputs[0].shape[0];
+        assert!(rows > 0, "Cannot evaluate the model without any row.");
+
+        for (output, target) in self.outputs.iter_mut().zip(&targets) {
+            for metric in output.metrics.iter_mut() {
+                metric.check_outputs(target.shape[1])?;
+                metric.reset();
+            }
+        }
+
+        let mut loss_sum = 0.0;
+        let mut output_loss_sums = vec![0.0; self.outputs.len()];
+        for batch in dataset::batch_indexes(rows, None) {
+            let batch_inputs = inputs.iter().map(|input| input.get_rows(&batch)).collect();
+            let batch_targets: Vec<Tensor> = targets.iter().map(|target| target.get_rows(&batch)).collect();
+
+            // the losses are averaged over the batch so we weight them by the batch size
+            let (loss, output_losses, predictions) = self.batch_losses(batch_inputs, &batch_targets, false);
+            loss_sum += loss * batch.len() as f64;
+            for (sum, output_loss) in output_loss_sums.iter_mut().zip(output_losses) {
+                *sum += output_loss * batch.len() as f64;
+            }
+            for ((output, target), predicted) in self.outputs.iter_mut().zip(&batch_targets).zip(&predictions) {
+                for metric in output.metrics.iter_mut() {
+                    metric.update(target, predicted);
+                }
+            }
+        }
+
+        let mut metrics = Vec::new();
+        for (output, sum) in self.outputs.iter().zip(output_loss_sums) {
+            metrics.push(MetricValue {
+                name: format!("{} loss", output.name),
+                value: sum / rows as f64,
+                per_output: vec![],
+            });
+            metrics.extend(output.metrics.iter().map(|metric| MetricValue {
+                name: format!("{} {}", output.name, metric.name()),
+                value: metric.result(),
+                per_output: metric.result_per_output(),
+            }));
+        }
+
+        Ok(Evaluation {
+            loss: loss_sum / rows as f64,
+            metrics,
+        })
+    }
+
+    /// Return the value of every output given one tensor per input
+    pub fn predict_tensors(&mut self, inputs: Vec<Tensor>) -> Vec<Tensor> {
+        let values = self.forward_propagation(inputs, false);
+        self.output_nodes.iter().map(|node| values[*node].clone()).collect()
+    }
+}
+
+impl Default for Graph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,91 @@
+/// Initial values of the parameters of layers
+use std::cmp;
+
+use crate::random::Rand;
+use crate::tensor::Tensor;
+
+/// Distribution of the initial values of a parameter. `fan_in` and `fan_out`
+/// are the number of inputs and outputs of a unit of the layer (e.g. the
+/// input and output units of a `Dense` layer).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Initializer {
+    /// Uniform in [-limit, limit] with limit = sqrt(6 / (fan_in + fan_out))
+    GlorotUniform,
+    /// Normal with stdev = sqrt(2 / (fan_in + fan_out))
+    GlorotNormal,
+    /// Uniform in [-limit, limit] with limit = sqrt(6 / fan_in), for ReLU layers
+    HeUniform,
+    /// Normal with stdev = sqrt(2 / fan_in), for ReLU layers
+    HeNormal,
+    /// Uniform in [-limit, limit] with limit = sqrt(3 / fan_in), for SELU layers
+    LeCunUniform,
+    /// Normal with stdev = sqrt(1 / fan_in), for SELU layers
+    LeCunNormal,
+    /// Orthogonal matrix (rows or columns are orthonormal) multiplied by `gain`
+    Orthogonal { gain: f64 },
+    Zeros,
+    Ones,
+    Constant(f64),
+    /// Normal whose values further than 2 stdev from the mean are drawn again
+    TruncatedNormal { mean: f64, stdev: f64 },
+}
+
+impl Initializer {
+    /// Create a tensor of shape `shape` filled with values of the distribution
+    pub fn initialize(&self, shape: Vec<usize>, fan_in: usize, fan_out: usize, seed: u32) -> Tensor {
+        let count: usize = shape.iter().product();
+        let (fan_in, fan_out) = (fan_in as f64, fan_out as f64);
+        let uniform = |limit: f64| Tensor::random_uniform(shape.to_vec(), -limit, limit, seed);
+        let normal = |stdev: f64| Tensor::random_normal(shape.to_vec(), 0.0, stdev, seed);
+
+        match *self {
+            Initializer::GlorotUniform => uniform((6.0 / (fan_in + fan_out)).sqrt()),
+            Initializer::GlorotNormal => normal((2.0 / (fan_in + fan_out)).sqrt()),
+            Initializer::HeUniform => uniform((6.0 / fan_in).sqrt()),
+            Initializer::HeNormal => normal((2.0 / fan_in).sqrt()),
+            Initializer::LeCunUniform => uniform((3.0 / fan_in).sqrt()),
+            Initializer::LeCunNormal => normal((1.0 / fan_in).sqrt()),
+            Initializer::Orthogonal { gain } => orthogonal(&mut Rand::new(seed), shape, gain),
+            Initializer::Zeros => Tensor::new(vec![0.0; count], shape),
+            Initializer::Ones => Tensor::new(vec![1.0; count], shape),
+            Initializer::Constant(value) => Tensor::new(vec![value; count], shape),
+            Initializer::TruncatedNormal { mean, stdev } => Tensor::truncated_normal(shape, mean, stdev, seed),
+        }
+    }
+}
+
+// Orthonormalize the columns of a random normal matrix (Gram-Schmidt). The
+// first axis of `shape` gives the rows, the other axes the columns.
+fn orthogonal(rng: &mut Rand, shape: Vec<usize>, gain: f64) -> Tensor {
+    let rows = shape[0];
+    let cols: usize = shape[1..].iter().product();
+    // as many orthonormal vectors as the smaller dimension
+    let (size, vectors) = (cmp::max(rows, cols), cmp::min(rows, cols));
+
+    let mut basis: Vec<Vec<f64>> = Vec::with_capacity(vectors);
+    while basis.len() < vectors {
+        let mut v: Vec<f64> = (0..size).map(|_| rng.standard_normal()).collect();
+        // two passes keep the vectors orthogonal despite rounding errors
+        for _ in 0..2 {
+            for q in &basis {
+                let dot: f64 = q.iter().zip(&v).map(|(a, b)| a * b).sum();
+                v.iter_mut().zip(q).for_each(|(x, q)| *x -= dot * q);
+            }
+        }
+        let norm = v.iter().map(|x| x * x).sum::<f64>().sqrt();
+        // draw again the (unlikely) vectors depending on the previous ones
+        if norm > 1e-8 {
+            basis.push(v.iter().map(|x| x / norm).collect());
+        }
+    }
+
+    let mut data = vec![0.0; rows * cols];
+    for (k, q) in basis.iter().enumerate() {
+        for (i, x) in q.iter().enumerate() {
+            // vectors are the columns if rows >= cols, the rows otherwise
+            let index = if rows >= cols { i * cols + k } else { k * cols + i };
+            data[index] = gain * x;
+        }
+    }
+    Tensor::new(data, shape)
+}
@@ -0,0 +1,166 @@
+use std::f64::consts::PI;
+
+use crate::layers::layer::LayerInfo;
+use crate::layers::layer::Layer;
+use crate::layers::layer::LearnableParams;
+use crate::tensor::Tensor;
+
+// Constants of SELU (self-normalizing for standardized inputs)
+const SELU_ALPHA: f64 = 1.673_263_242_354_377_3;
+const SELU_SCALE: f64 = 1.050_700_987_355_480_5;
+
+/// Element-wise function applied by an `Activation` layer
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ActivationFn {
+    /// x if x > 0, slope * x otherwise
+    LeakyReLU { slope: f64 },
+    /// x if x > 0, alpha * (exp(x) - 1) otherwise
+    ELU { alpha: f64 },
+    /// ELU scaled by 1.0507 with alpha = 1.6733
+    SELU,
+    /// x * Φ(x) where Φ is the standard normal cumulative distribution
+    GELU,
+    /// x * sigmoid(beta * x)
+    Swish { beta: f64 },
+    /// ln(1 + exp(x))
+    Softplus,
+    /// x * tanh(softplus(x))
+    Mish,
+}
+
+impl ActivationFn {
+    pub fn value(&self, x: f64) -> f64 {
+        match *self {
+            ActivationFn::LeakyReLU { slope } => if x > 0.0 { x } else { slope * x },
+            ActivationFn::ELU { alpha } => if x > 0.0 { x } else { alpha * x.exp_m1() },
+            ActivationFn::SELU => SELU_SCALE * if x > 0.0 { x } else { SELU_ALPHA * x.exp_m1() },
+            ActivationFn::GELU => 0.5 * x * (1.0 + erf(x / 2.0_f64.sqrt())),
+            ActivationFn::Swish { beta } => x * sigmoid(beta * x),
+            ActivationFn::Softplus => softplus(x),
+            ActivationFn::Mish => x * softplus(x).tanh(),
+        }
+    }
+
+    pub fn derivative(&self, x: f64) -> f64 {
+        match *self {
+            ActivationFn::LeakyReLU { slope } => if x > 0.0 { 1.0 } else { slope },
+            ActivationFn::ELU { alpha } => if x > 0.0 { 1.0 } else { alpha * x.exp() },
+            ActivationFn::SELU => SELU_SCALE * if x > 0.0 { 1.0 } else { SELU_ALPHA * x.exp() },
+            ActivationFn::GELU => {
+                let cdf = 0.5 * (1.0 + erf(x / 2.0_f64.sqrt()));
+                let pdf = (-0.5 * x * x).exp() / (2.0 * PI).sqrt();
+                cdf + x * pdf
+            }
+            ActivationFn::Swish { beta } => {
+                let s = sigmoid(beta * x);
+                s + beta * x * s * (1.0 - s)
+            }
+            ActivationFn::Softplus => sigmoid(x),
+            ActivationFn::Mish => {
+                let t = softplus(x).tanh();
+                t + x * (1.0 - t * t) * sigmoid(x)
+            }
+        }
+    }
+
+    fn name(&self) -> String {
+        match self {
+            ActivationFn::LeakyReLU { slope } => format!("LeakyReLU {}", slope),
+            ActivationFn::ELU { alpha } => format!("ELU {}", alpha),
+            ActivationFn::Swish { beta } => format!("Swish {}", beta),
+            _ => format!("{:?}", self),
+        }
+    }
+}
+
+// Sigmoid without overflow of exp for large |x|
+fn sigmoid(x: f64) -> f64 {
+    if x >= 0.0 {
+        1.0 / (1.0 + (-x).exp())
+    } else {
+        let e = x.exp();
+        e / (1.0 + e)
+    }
+}
+
+// ln(1 + exp(x)) = max(x, 0) + ln(1 + exp(-|x|))
+fn softplus(x: f64) -> f64 {
+    x.max(0.0) + (-x.abs()).exp().ln_1p()
+}
+
+// Error function from the series erf(x) = 2/sqrt(pi) exp(-x^2) sum 2^n x^(2n+1) / (1*3*...*(2n+1))
+// whose terms are all positive (no cancellation), accurate to the f64 precision
+fn erf(x: f64) -> f64 {
+    if x.abs() > 6.0 {
+        // 1 - erf(6) < 1e-16
+        return x.signum();
+    }
+    let mut term = x;
+    let mut sum = x;
+    let mut n = 0.0;
+    while term.abs() > 1e-17 * sum.abs() {
+        n += 1.0;
+        term *= 2.0 * x * x / (2.0 * n + 1.0);
+        sum += term;
+    }
+    2.0 / PI.sqrt() * (-x * x).exp() * sum
+}
+
+/// Layer applying an `ActivationFn` to each element of its inputs
+pub struct Activation {
+    function: ActivationFn,
+    input: Tensor,
+    output_shape: Vec<usize>,
+}
+
+impl Activation {
+    pub fn new(function: ActivationFn) -> Activation {
+        Activation {
+            function,
+            input: Tensor::new(vec![], vec![]),
+            output_shape: vec![],
+        }
+    }
+}
+
+impl Layer for Activation {
+    fn get_info(&self) -> LayerInfo {
+        LayerInfo {
+            layer_type: self.function.name(),
+            output_shape: self.output_shape.to_vec(),
+            trainable_param: 0,
+            non_trainable_param: 0,
+        }
+    }
+
+    fn build(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, String> {
+        self.output_shape = input_shape.to_vec();
+        Ok(self.output_shape.to_vec())
+    }
+
+    fn forward(&mut self, input: Tensor, _training: bool) -> Tensor {
+        let data = input.data.iter().map(|x| self.function.value(*x)).collect();
+        let output = Tensor::new(data, input.shape.to_vec());
+        self.input = input;
+        output
+    }
+
+    fn backward(&mut self, gradient: &Tensor) -> Tensor {
+        let data = gradient.data.iter().zip(&self.input.data)
+            .map(|(grad, x)| grad * self.function.derivative(*x))
+            .collect();
+        Tensor::new(data, gradient.shape.to_vec())
+    }
+
+    fn get_params_list(&self) -> Vec<LearnableParams> {
+        vec![]
+    }
+
+    fn get_grad(&self, _param: &LearnableParams) -> &Tensor {
+        panic!("Layer does not have learnable parameters.")
+    }
+
+    fn get_param(&mut self, _param: &LearnableParams) -> &mut Tensor {
+        panic!("Layer does not have learnable parameters.")
+    }
+}
@@ -0,0 +1,68 @@
+use crate::layers::layer::LayerInfo;
+use crate::layers::layer::Layer;
+use crate::layers::layer::LearnableParams;
+use crate::regularizers::Regularizer;
+use crate::tensor::Tensor;
+
+/// Identity layer penalizing its inputs (the outputs of the previous layer).
+/// The penalty is averaged over the samples of the batch, like the losses.
+pub struct ActivityRegularization {
+    regularizer: Regularizer,
+    input: Tensor,
+    penalty: f64,
+    output_shape: Vec<usize>,
+}
+
+impl ActivityRegularization {
+    pub fn new(regularizer: Regularizer) -> ActivityRegularization {
+        ActivityRegularization {
+            regularizer,
+            input: Tensor::new(vec![], vec![]),
+            penalty: 0.0,
+            output_shape: vec![],
+        }
+    }
+}
+
+impl Layer for ActivityRegularization {
+    fn get_info(&self) -> LayerInfo {
+        LayerInfo {
+            layer_type: "ActivityRegularization".to_string(),
+            output_shape: self.output_shape.to_vec(),
+            trainable_param: 0,
+            non_trainable_param: 0,
+        }
+    }
+
+    fn build(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, String> {
+        self.output_shape = input_shape.to_vec();
+        Ok(self.output_shape.to_vec())
+    }
+
+    fn forward(&mut self, input: Tensor, _training: bool) -> Tensor {
+        self.penalty = self.regularizer.penalty(&input) / input.shape[0] as f64;
+        self.input = input.clone();
+        input
+    }
+
+    fn backward(&mut self, gradient: &Tensor) -> Tensor {
+        let batch = self.input.shape[0] as f64;
+        gradient + &((1.0 / batch) * self.regularizer.gradient(&self.input))
+    }
+
+    fn get_params_list(&self) -> Vec<LearnableParams> {
+        vec![]
+    }
+
+    fn get_grad(&self, _param: &LearnableParams) -> &Tensor {
+        panic!("Layer does not have learnable parameters.")
+    }
+
+    fn get_param(&mut self, _param: &LearnableParams) -> &mut Tensor {
+        panic!("Layer does not have learnable parameters.")
+    }
+
+    fn get_regularization_loss(&self) -> f64 {
+        self.penalty
+    }
+}
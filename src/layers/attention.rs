@@ -0,0 +1,260 @@
+use crate::layers::layer::LayerInfo;
+use crate::layers::layer::Layer;
+use crate::layers::layer::LearnableParams;
+use crate::layers::dense::Dense;
+use crate::random::SeedSequence;
+use crate::tensor::Tensor;
+
+/// Multi-head self-attention over inputs of shape [batch, time, model_dim].
+///
+/// The inputs are projected to queries, keys and values (`Dense` sublayers 0,
+/// 1 and 2), split in `heads` heads of size model_dim / heads and combined with
+/// scaled dot-product attention: softmax(Q K^T / sqrt(head_dim)) V.
+/// The heads are concatenated and projected by the output sublayer 3.
+/// Parameters are `LearnableParams::Sublayer(index, Weights or Biases)`.
+pub struct MultiHeadAttention {
+    heads: usize,
+    // query, key, value and output projections
+    projections: Vec<Dense>,
+    causal: bool,
+    mask_padding: bool,
+    padding_mask: Option<Tensor>,
+    // saved during the forward pass
+    queries: Tensor,
+    keys: Tensor,
+    values: Tensor,
+    // attention weights [batch, heads, time (query), time (key)]
+    attention: Vec<f64>,
+    input_shape: Vec<usize>,
+    trainable: bool,
+    output_shape: Vec<usize>,
+}
+
+impl MultiHeadAttention {
+    /// Create an attention layer without masks
+    pub fn new(model_dim: usize, heads: usize, seed: u32) -> MultiHeadAttention {
+        assert!(heads > 0 && model_dim.is_multiple_of(heads), "The model dimension ({}) must be a multiple of the number of heads ({}).", model_dim, heads);
+        let seeds = SeedSequence::new(seed);
+        MultiHeadAttention {
+            heads,
+            projections: (0..4).map(|i| Dense::new(model_dim, model_dim, seeds.child(i).seed())).collect(),
+            causal: false,
+            mask_padding: false,
+            padding_mask: None,
+            queries: Tensor::new(vec![], vec![]),
+            keys: Tensor::new(vec![], vec![]),
+            values: Tensor::new(vec![], vec![]),
+            attention: vec![],
+            input_shape: vec![],
+            trainable: true,
+            output_shape: vec![],
+        }
+    }
+
+    /// Prevent each position from attending to the following positions
+    pub fn with_causal_mask(mut self, causal: bool) -> MultiHeadAttention {
+        self.causal = causal;
+        self
+    }
+
+    /// Ignore the keys whose input vector is only made of zeros (padding)
+    pub fn with_padding_mask(mut self, mask_padding: bool) -> MultiHeadAttention {
+        self.mask_padding = mask_padding;
+        self
+    }
+
+    /// Set the keys to ignore for the next forward passes: `mask` has the
+    /// shape [batch, time] with 1 for the positions to keep and 0 for padding
+    pub fn set_padding_mask(&mut self, mask: Option<Tensor>) {
+        self.padding_mask = mask;
+    }
+
+    /// Attention weights of the last forward pass [batch, heads, time, time]
+    pub fn get_attention(&self) -> Tensor {
+        let (batch, time) = (self.input_shape[0], self.input_shape[1]);
+        Tensor::new(self.attention.to_vec(), vec![batch, self.heads, time, time])
+    }
+
+    pub(crate) fn heads(&self) -> usize {
+        self.heads
+    }
+
+    fn model_dim(&self) -> usize {
+        self.projections[0].get_info().output_shape[0]
+    }
+
+    // Return for each (sample, position) whether the key can be attended
+    fn key_mask(&self, input: &Tensor) -> Vec<bool> {
+        let dim = input.shape[2];
+        let mut mask: Vec<bool> = match &self.padding_mask {
+            Some(padding_mask) => {
+                assert_eq!(padding_mask.data.len(), input.shape[0] * input.shape[1], "Padding mask must have the shape [batch, time].");
+                padding_mask.data.iter().map(|value| *value != 0.0).collect()
+            }
+            None => vec![true; input.shape[0] * input.shape[1]],
+        };
+        if self.mask_padding {
+            for (keep, vector) in mask.iter_mut().zip(input.data.chunks(dim)) {
+                *keep = *keep && vector.iter().any(|value| *value != 0.0);
+            }
+        }
+        mask
+    }
+}
+
+impl Layer for MultiHeadAttention {
+    fn get_info(&self) -> LayerInfo {
+        // 4 projections with [model_dim, model_dim] weights and model_dim biases
+        let dim = self.model_dim();
+        LayerInfo {
+            layer_type: format!("MultiHeadAttention {}", self.heads),
+            output_shape: self.output_shape.to_vec(),
+            trainable_param: 4 * (dim * dim + dim),
+            non_trainable_param: 0,
+        }
+    }
+
+    fn build(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, String> {
+        let dim = self.model_dim();
+        match input_shape {
+            [_, features] if *features == dim => (),
+            _ => return Err(format!("expected an input shape [time, {}], got {:?}", dim, input_shape)),
+        }
+        for projection in self.projections.iter_mut() {
+            projection.build(&[dim])?;
+        }
+        self.output_shape = input_shape.to_vec();
+        Ok(self.output_shape.to_vec())
+    }
+
+    fn forward(&mut self, input: Tensor, training: bool) -> Tensor {
+        let dim = self.model_dim();
+        assert!(input.shape.len() == 3 && input.shape[2] == dim,
+            "MultiHeadAttention expects inputs of shape [batch, time, {}], got {:?}.", dim, input.shape);
+        let (batch, time) = (input.shape[0], input.shape[1]);
+        let head_dim = dim / self.heads;
+        let scale = 1.0 / (head_dim as f64).sqrt();
+        let key_mask = self.key_mask(&input);
+
+        let flat = Tensor::new(input.data, vec![batch * time, dim]);
+        self.queries = self.projections[0].forward(flat.clone(), training);
+        self.keys = self.projections[1].forward(flat.clone(), training);
+        self.values = self.projections[2].forward(flat, training);
+        let (q, k, v) = (&self.queries.data, &self.keys.data, &self.values.data);
+
+        // index of the element `j` of head `h` at position `t` of sample `b`
+        let at = |b: usize, t: usize, h: usize, j: usize| (b * time + t) * dim + h * head_dim + j;
+
+        let mut attention = vec![0.0; batch * self.heads * time * time];
+        let mut concat = vec![0.0; batch * time * dim];
+        for b in 0..batch {
+            for h in 0..self.heads {
+                for i in 0..time {
+                    let row = &mut attention[((b * self.heads + h) * time + i) * time..][..time];
+                    let allowed = |j: usize| key_mask[b * time + j] && (!self.causal || j <= i);
+
+                    // softmax of the scaled scores over the allowed keys only
+                    let mut max = f64::NEG_INFINITY;
+                    for (j, score) in row.iter_mut().enumerate() {
+                        if allowed(j) {
+                            *score = (0..head_dim).map(|n| q[at(b, i, h, n)] * k[at(b, j, h, n)]).sum::<f64>() * scale;
+                            max = max.max(*score);
+                        }
+                    }
+                    let mut sum = 0.0;
+                    for (j, score) in row.iter_mut().enumerate() {
+                        *score = if allowed(j) { (*score - max).exp() } else { 0.0 };
+                        sum += *score;
+                    }
+                    if sum > 0.0 {
+                        for (j, weight) in row.iter_mut().enumerate() {
+                            *weight /= sum;
+                            for n in 0..head_dim {
+                                concat[at(b, i, h, n)] += *weight * v[at(b, j, h, n)];
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        self.attention = attention;
+        self.input_shape = vec![batch, time, dim];
+
+        let output = self.projections[3].forward(Tensor::new(concat, vec![batch * time, dim]), training);
+        Tensor::new(output.data, vec![batch, time, dim])
+    }
+
+    fn backward(&mut self, gradient: &Tensor) -> Tensor {
+        let (batch, time, dim) = (self.input_shape[0], self.input_shape[1], self.input_shape[2]);
+        let head_dim = dim / self.heads;
+        let scale = 1.0 / (head_dim as f64).sqrt();
+        let at = |b: usize, t: usize, h: usize, j: usize| (b * time + t) * dim + h * head_dim + j;
+
+        let concat_grad = self.projections[3].backward(&Tensor::new(gradient.data.to_vec(), vec![batch * time, dim]));
+        let (q, k, v) = (&self.queries.data, &self.keys.data, &self.values.data);
+
+        let mut q_grad = vec![0.0; batch * time * dim];
+        let mut k_grad = vec![0.0; batch * time * dim];
+        let mut v_grad = vec![0.0; batch * time * dim];
+        for b in 0..batch {
+            for h in 0..self.heads {
+                for i in 0..time {
+                    let row = &self.attention[((b * self.heads + h) * time + i) * time..][..time];
+
+                    // gradient of the attention weights then of the scores (softmax)
+                    let weights_grad: Vec<f64> = (0..time)
+                        .map(|j| (0..head_dim).map(|n| concat_grad.data[at(b, i, h, n)] * v[at(b, j, h, n)]).sum())
+                        .collect();
+                    let dot: f64 = row.iter().zip(&weights_grad).map(|(p, g)| p * g).sum();
+
+                    for j in 0..time {
+                        let score_grad = row[j] * (weights_grad[j] - dot) * scale;
+                        for n in 0..head_dim {
+                            v_grad[at(b, j, h, n)] += row[j] * concat_grad.data[at(b, i, h, n)];
+                            q_grad[at(b, i, h, n)] += score_grad * k[at(b, j, h, n)];
+                            k_grad[at(b, j, h, n)] += score_grad * q[at(b, i, h, n)];
+                        }
+                    }
+                }
+            }
+        }
+
+        let shape = vec![batch * time, dim];
+        let input_grad = self.projections[0].backward(&Tensor::new(q_grad, shape.to_vec()))
+            + self.projections[1].backward(&Tensor::new(k_grad, shape.to_vec()))
+            + self.projections[2].backward(&Tensor::new(v_grad, shape));
+        Tensor::new(input_grad.data, self.input_shape.to_vec())
+    }
+
+    fn get_params_list(&self) -> Vec<LearnableParams> {
+        let mut params = Vec::new();
+        for (index, projection) in self.projections.iter().enumerate() {
+            for param in projection.get_params_list() {
+                params.push(LearnableParams::Sublayer(index, Box::new(param)));
+            }
+        }
+        params
+    }
+
+    fn get_grad(&self, param: &LearnableParams) -> &Tensor {
+        match param {
+            LearnableParams::Sublayer(index, param) if *index < 4 => self.projections[*index].get_grad(param),
+            _ => panic!("MultiHeadAttention parameters are LearnableParams::Sublayer(0 to 3, _), got {:?}.", param),
+        }
+    }
+
+    fn get_param(&mut self, param: &LearnableParams) -> &mut Tensor {
+        match param {
+            LearnableParams::Sublayer(index, param) if *index < 4 => self.projections[*index].get_param(param),
+            _ => panic!("MultiHeadAttention parameters are LearnableParams::Sublayer(0 to 3, _), got {:?}.", param),
+        }
+    }
+
+    fn is_trainable(&self) -> bool {
+        self.trainable
+    }
+
+    fn set_trainable(&mut self, trainable: bool) {
+        self.trainable = trainable;
+    }
+}
@@ -0,0 +1,203 @@
+use crate::layers::layer::LayerInfo;
+use crate::layers::layer::Layer;
+use crate::layers::layer::LearnableParams;
+use crate::layers::DataFormat;
+use crate::tensor::Tensor;
+
+/// Batch normalization layer. Each channel is normalized with the mean and
+/// the variance computed over the batch (and the spatial positions), then
+/// scaled by `gamma` and shifted by `beta`.
+///
+/// Inputs are [batch, features] or multi-dimensional tensors whose channels
+/// axis is given by the `DataFormat` (axis 1 or the last axis).
+/// During inference (and when the layer is frozen), the moving averages of
+/// the batch statistics are used.
+pub struct BatchNorm {
+    momentum: f64,
+    epsilon: f64,
+    data_format: DataFormat,
+    gamma: Tensor,
+    beta: Tensor,
+    gamma_grad: Tensor,
+    beta_grad: Tensor,
+    moving_mean: Tensor,
+    moving_variance: Tensor,
+    // normalized input and standard deviation of each channel
+    // saved during the forward pass
+    normalized: Tensor,
+    stdev: Vec<f64>,
+    training: bool,
+    trainable: bool,
+    output_shape: Vec<usize>,
+}
+
+impl BatchNorm {
+    /// Moving statistics are updated as
+    /// `moving = momentum * moving + (1 - momentum) * batch_statistic`
+    pub fn new(momentum: f64, epsilon: f64, data_format: DataFormat) -> BatchNorm {
+        assert!((0.0..=1.0).contains(&momentum), "Momentum must be between 0 and 1.");
+        BatchNorm {
+            momentum,
+            epsilon,
+            data_format,
+            gamma: Tensor::new(vec![], vec![]),
+            beta: Tensor::new(vec![], vec![]),
+            gamma_grad: Tensor::new(vec![], vec![]),
+            beta_grad: Tensor::new(vec![], vec![]),
+            moving_mean: Tensor::new(vec![], vec![]),
+            moving_variance: Tensor::new(vec![], vec![]),
+            normalized: Tensor::new(vec![], vec![]),
+            stdev: vec![],
+            training: false,
+            trainable: true,
+            output_shape: vec![],
+        }
+    }
+
+    // Create the parameters the first time the number of channels is known
+    fn init_params(&mut self, channels: usize) {
+        if self.gamma.data.len() != channels {
+            self.gamma = Tensor::one(vec![1, channels]);
+            self.beta = Tensor::zero(vec![1, channels]);
+            self.moving_mean = Tensor::zero(vec![1, channels]);
+            self.moving_variance = Tensor::one(vec![1, channels]);
+        }
+    }
+
+    // Return the number of channels and a function giving the channel of
+    // each flat index for a tensor of shape `shape` (with the batch dimension)
+    fn channels(&self, shape: &[usize]) -> (usize, impl Fn(usize) -> usize) {
+        let (channels, inner) = match self.data_format {
+            DataFormat::ChannelsFirst => (shape[1], shape[2..].iter().product::<usize>()),
+            DataFormat::ChannelsLast => (shape[shape.len() - 1], 1),
+        };
+        (channels, move |index: usize| (index / inner) % channels)
+    }
+}
+
+impl Layer for BatchNorm {
+    fn get_info(&self) -> LayerInfo {
+        LayerInfo {
+            layer_type: "BatchNorm".to_string(),
+            output_shape: self.output_shape.to_vec(),
+            trainable_param: self.gamma.data.len() + self.beta.data.len(),
+            non_trainable_param: self.moving_mean.data.len() + self.moving_variance.data.len(),
+        }
+    }
+
+    fn build(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, String> {
+        if input_shape.is_empty() {
+            return Err("expected at least a 1D input shape".to_string());
+        }
+        let channels = match self.data_format {
+            DataFormat::ChannelsFirst => input_shape[0],
+            DataFormat::ChannelsLast => input_shape[input_shape.len() - 1],
+        };
+        self.init_params(channels);
+        self.output_shape = input_shape.to_vec();
+        Ok(self.output_shape.to_vec())
+    }
+
+    fn forward(&mut self, input: Tensor, training: bool) -> Tensor {
+        assert!(input.shape.len() >= 2, "BatchNorm expects at least 2D inputs, got shape {:?}.", input.shape);
+        let (channels, channel_of) = self.channels(&input.shape);
+        self.init_params(channels);
+        // a frozen layer normalizes with its moving statistics and keeps them
+        let training = training && self.trainable;
+
+        let (mean, variance) = if training {
+            // statistics of the batch for each channel
+            let count = (input.data.len() / channels) as f64;
+            let mut mean = vec![0.0; channels];
+            for (i, x) in input.data.iter().enumerate() {
+                mean[channel_of(i)] += x / count;
+            }
+            let mut variance = vec![0.0; channels];
+            for (i, x) in input.data.iter().enumerate() {
+                let c = channel_of(i);
+                variance[c] += (x - mean[c]).powi(2) / count;
+            }
+
+            for c in 0..channels {
+                self.moving_mean.data[c] = self.momentum * self.moving_mean.data[c] + (1.0 - self.momentum) * mean[c];
+                self.moving_variance.data[c] = self.momentum * self.moving_variance.data[c] + (1.0 - self.momentum) * variance[c];
+            }
+            (mean, variance)
+        } else {
+            (self.moving_mean.data.to_vec(), self.moving_variance.data.to_vec())
+        };
+
+        self.training = training;
+        self.stdev = variance.iter().map(|v| (v + self.epsilon).sqrt()).collect();
+        let mut normalized = input.data;
+        let mut output = vec![0.0; normalized.len()];
+        for (i, x) in normalized.iter_mut().enumerate() {
+            let c = channel_of(i);
+            *x = (*x - mean[c]) / self.stdev[c];
+            output[i] = self.gamma.data[c] * *x + self.beta.data[c];
+        }
+        self.normalized = Tensor::new(normalized, input.shape.to_vec());
+
+        Tensor::new(output, input.shape)
+    }
+
+    fn backward(&mut self, gradient: &Tensor) -> Tensor {
+        let (channels, channel_of) = self.channels(&gradient.shape);
+        let count = (gradient.data.len() / channels) as f64;
+
+        let mut beta_grad = vec![0.0; channels];
+        let mut gamma_grad = vec![0.0; channels];
+        for (i, g) in gradient.data.iter().enumerate() {
+            let c = channel_of(i);
+            beta_grad[c] += g;
+            gamma_grad[c] += g * self.normalized.data[i];
+        }
+
+        let input_grad = gradient.data.iter().enumerate().map(|(i, g)| {
+            let c = channel_of(i);
+            if self.training {
+                // the batch statistics depend on the input:
+                // dx = gamma / (N * stdev) * (N * g - sum(g) - x_hat * sum(g * x_hat))
+                self.gamma.data[c] / (count * self.stdev[c])
+                    * (count * g - beta_grad[c] - self.normalized.data[i] * gamma_grad[c])
+            } else {
+                self.gamma.data[c] / self.stdev[c] * g
+            }
+        }).collect();
+
+        self.gamma_grad = Tensor::new(gamma_grad, self.gamma.shape.to_vec());
+        self.beta_grad = Tensor::new(beta_grad, self.beta.shape.to_vec());
+
+        Tensor::new(input_grad, gradient.shape.to_vec())
+    }
+
+    fn get_params_list(&self) -> Vec<LearnableParams> {
+        vec![LearnableParams::Gamma, LearnableParams::Beta]
+    }
+
+    fn get_grad(&self, param: &LearnableParams) -> &Tensor {
+        match param {
+            LearnableParams::Gamma => &self.gamma_grad,
+            LearnableParams::Beta => &self.beta_grad,
+            _ => panic!("BatchNorm does not have {:?} gradients.", param),
+        }
+    }
+
+    fn get_param(&mut self, param: &LearnableParams) -> &mut Tensor {
+        match param {
+            LearnableParams::Gamma => &mut self.gamma,
+            LearnableParams::Beta => &mut self.beta,
+            LearnableParams::MovingMean => &mut self.moving_mean,
+            LearnableParams::MovingVariance => &mut self.moving_variance,
+            _ => panic!("BatchNorm does not have {:?} parameters.", param),
+        }
+    }
+
+    fn is_trainable(&self) -> bool {
+        self.trainable
+    }
+
+    fn set_trainable(&mut self, trainable: bool) {
+        self.trainable = trainable;
+    }
+}
@@ -0,0 +1,163 @@
+use crate::layers::layer::LayerInfo;
+use crate::layers::layer::Layer;
+use crate::layers::layer::LearnableParams;
+use crate::layers::recurrent::{Recurrent, time_slice, set_time_slice};
+use crate::tensor::Tensor;
+
+/// How the outputs of both directions of a `Bidirectional` layer are combined
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MergeMode {
+    /// Concatenate the features: the output has 2 * units features
+    Concat,
+    /// Add the outputs
+    Sum,
+}
+
+/// Run a recurrent layer over the sequence in both directions and merge
+/// the outputs. The parameters of the forward and backward layers are
+/// `LearnableParams::Sublayer(0, _)` and `LearnableParams::Sublayer(1, _)`.
+pub struct Bidirectional {
+    forward: Recurrent,
+    backward: Recurrent,
+    merge_mode: MergeMode,
+    trainable: bool,
+    output_shape: Vec<usize>,
+}
+
+impl Bidirectional {
+    /// `backward` processes the time steps in reverse order
+    pub fn new(forward: Recurrent, backward: Recurrent, merge_mode: MergeMode) -> Bidirectional {
+        assert_eq!(forward.units(), backward.units(), "Both directions must have the same number of units.");
+        assert_eq!(forward.returns_sequences(), backward.returns_sequences(), "Both directions must return sequences or not.");
+        Bidirectional {
+            forward,
+            backward: backward.with_go_backwards(true),
+            merge_mode,
+            trainable: true,
+            output_shape: vec![],
+        }
+    }
+
+    // Reverse the time axis of a [batch, time, units] tensor
+    fn reverse_time(tensor: &Tensor) -> Tensor {
+        let time = tensor.shape[1];
+        let mut data = vec![0.0; tensor.data.len()];
+        for t in 0..time {
+            set_time_slice(&mut data, time, time - 1 - t, &time_slice(tensor, t));
+        }
+        Tensor::new(data, tensor.shape.to_vec())
+    }
+
+    fn sublayer(&self, index: usize) -> &Recurrent {
+        match index {
+            0 => &self.forward,
+            1 => &self.backward,
+            _ => panic!("Bidirectional only has 2 sublayers."),
+        }
+    }
+}
+
+// Split the last axis of `tensor` in two halves
+fn split_last_axis(tensor: &Tensor) -> (Tensor, Tensor) {
+    let width = tensor.shape[tensor.shape.len() - 1] / 2;
+    let mut first = Vec::with_capacity(tensor.data.len() / 2);
+    let mut second = Vec::with_capacity(tensor.data.len() / 2);
+    for row in tensor.data.chunks(2 * width) {
+        first.extend_from_slice(&row[..width]);
+        second.extend_from_slice(&row[width..]);
+    }
+    let mut shape = tensor.shape.to_vec();
+    *shape.last_mut().unwrap() = width;
+    (Tensor::new(first, shape.to_vec()), Tensor::new(second, shape))
+}
+
+impl Layer for Bidirectional {
+    fn get_info(&self) -> LayerInfo {
+        let forward = self.forward.get_info();
+        LayerInfo {
+            layer_type: format!("Bidirectional {}", forward.layer_type),
+            output_shape: self.output_shape.to_vec(),
+            trainable_param: forward.trainable_param + self.backward.get_info().trainable_param,
+            non_trainable_param: 0,
+        }
+    }
+
+    fn build(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, String> {
+        let mut shape = self.forward.build(input_shape)?;
+        self.backward.build(input_shape)?;
+        if self.merge_mode == MergeMode::Concat {
+            *shape.last_mut().unwrap() *= 2;
+        }
+        self.output_shape = shape;
+        Ok(self.output_shape.to_vec())
+    }
+
+    fn forward(&mut self, input: Tensor, training: bool) -> Tensor {
+        let forward = self.forward.forward(input.clone(), training);
+        let mut backward = self.backward.forward(input, training);
+        // align the backward sequence with the forward one
+        if self.backward.returns_sequences() {
+            backward = Bidirectional::reverse_time(&backward);
+        }
+
+        match self.merge_mode {
+            MergeMode::Sum => forward + backward,
+            MergeMode::Concat => {
+                let width = forward.shape[forward.shape.len() - 1];
+                let mut data = Vec::with_capacity(2 * forward.data.len());
+                for (f, b) in forward.data.chunks(width).zip(backward.data.chunks(width)) {
+                    data.extend_from_slice(f);
+                    data.extend_from_slice(b);
+                }
+                let mut shape = forward.shape.to_vec();
+                *shape.last_mut().unwrap() *= 2;
+                Tensor::new(data, shape)
+            }
+        }
+    }
+
+    fn backward(&mut self, gradient: &Tensor) -> Tensor {
+        let (forward_grad, mut backward_grad) = match self.merge_mode {
+            MergeMode::Sum => (gradient.clone(), gradient.clone()),
+            MergeMode::Concat => split_last_axis(gradient),
+        };
+        if self.backward.returns_sequences() {
+            backward_grad = Bidirectional::reverse_time(&backward_grad);
+        }
+
+        self.forward.backward(&forward_grad) + self.backward.backward(&backward_grad)
+    }
+
+    fn get_params_list(&self) -> Vec<LearnableParams> {
+        let mut params = Vec::new();
+        for index in 0..2 {
+            for param in self.sublayer(index).get_params_list() {
+                params.push(LearnableParams::Sublayer(index, Box::new(param)));
+            }
+        }
+        params
+    }
+
+    fn get_grad(&self, param: &LearnableParams) -> &Tensor {
+        match param {
+            LearnableParams::Sublayer(index, param) => self.sublayer(*index).get_grad(param),
+            _ => panic!("Bidirectional parameters are LearnableParams::Sublayer, got {:?}.", param),
+        }
+    }
+
+    fn get_param(&mut self, param: &LearnableParams) -> &mut Tensor {
+        match param {
+            LearnableParams::Sublayer(0, param) => self.forward.get_param(param),
+            LearnableParams::Sublayer(1, param) => self.backward.get_param(param),
+            _ => panic!("Bidirectional parameters are LearnableParams::Sublayer(0 or 1, _), got {:?}.", param),
+        }
+    }
+
+    fn is_trainable(&self) -> bool {
+        self.trainable
+    }
+
+    fn set_trainable(&mut self, trainable: bool) {
+        self.trainable = trainable;
+    }
+}
@@ -0,0 +1,137 @@
+use crate::layers::layer::LayerInfo;
+use crate::layers::layer::Layer;
+use crate::layers::layer::LearnableParams;
+use crate::initializers::Initializer;
+use crate::layers::conv2d::Conv2D;
+use crate::layers::{DataFormat, Padding};
+use crate::tensor::Tensor;
+
+/// 1D convolution layer over temporal data of shape [batch, time, channels].
+/// Flat inputs of shape [batch, time * channels] (time major, as loaded by
+/// `Dataset`) are also accepted. The output shape is [batch, time, filters].
+///
+/// It is computed as a 2D convolution over [batch, time, 1, channels]
+/// so the kernels have the shape [filters, channels, kernel_size, 1].
+pub struct Conv1D {
+    conv: Conv2D,
+    input_channels: usize,
+    kernel_size: usize,
+    input_shape: Vec<usize>,
+    trainable: bool,
+    output_shape: Vec<usize>,
+}
+
+impl Conv1D {
+    /// Create a convolution with a stride and a dilation of 1 and no padding.
+    /// Use the `with_*` methods to change them.
+    pub fn new(input_channels: usize, filters: usize, kernel_size: usize, seed: u32) -> Conv1D {
+        Conv1D {
+            conv: Conv2D::new(input_channels, filters, (kernel_size, 1), seed)
+                .with_data_format(DataFormat::ChannelsLast),
+            input_channels,
+            kernel_size,
+            input_shape: vec![],
+            trainable: true,
+            output_shape: vec![],
+        }
+    }
+
+    /// Initialize the kernels with `initializer` (Glorot uniform by default, as `Dense`)
+    pub fn with_kernel_initializer(mut self, initializer: Initializer) -> Conv1D {
+        self.conv = self.conv.with_kernel_initializer(initializer);
+        self
+    }
+
+    pub fn with_stride(mut self, stride: usize) -> Conv1D {
+        self.conv = self.conv.with_stride((stride, 1));
+        self
+    }
+
+    /// Padding is applied to the time axis only (`Padding::Explicit(time, _)`)
+    pub fn with_padding(mut self, padding: Padding) -> Conv1D {
+        let padding = match padding {
+            Padding::Explicit(time, _) => Padding::Explicit(time, 0),
+            padding => padding,
+        };
+        self.conv = self.conv.with_padding(padding);
+        self
+    }
+
+    pub fn with_dilation(mut self, dilation: usize) -> Conv1D {
+        self.conv = self.conv.with_dilation((dilation, 1));
+        self
+    }
+
+    /// Get the output shape [time, filters] for an input
+    /// of shape [time, channels]
+    pub fn compute_output_shape(&self, input_shape: &[usize]) -> Vec<usize> {
+        let output_shape = self.conv.compute_output_shape(&[input_shape[0], 1, input_shape[1]]);
+        vec![output_shape[0], output_shape[2]]
+    }
+}
+
+impl Layer for Conv1D {
+    fn get_info(&self) -> LayerInfo {
+        let info = self.conv.get_info();
+        LayerInfo {
+            layer_type: format!("Conv1D {}", self.kernel_size),
+            output_shape: self.output_shape.to_vec(),
+            trainable_param: info.trainable_param,
+            non_trainable_param: info.non_trainable_param,
+        }
+    }
+
+    fn build(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, String> {
+        let (time, channels) = match input_shape {
+            [time, channels] => (*time, *channels),
+            [size] if size.is_multiple_of(self.input_channels) => (size / self.input_channels, self.input_channels),
+            _ => return Err(format!("expected an input shape [time, {}] or [time * {}], got {:?}",
+                self.input_channels, self.input_channels, input_shape)),
+        };
+
+        let output_shape = self.conv.build(&[time, 1, channels])?;
+        self.output_shape = vec![output_shape[0], output_shape[2]];
+        Ok(self.output_shape.to_vec())
+    }
+
+    fn forward(&mut self, input: Tensor, training: bool) -> Tensor {
+        let channels = self.input_channels;
+        let batch = input.shape[0];
+        let time = match input.shape.len() {
+            3 => input.shape[1],
+            2 if input.shape[1].is_multiple_of(channels) => input.shape[1] / channels,
+            _ => panic!("Conv1D expects inputs of shape [batch, time, {}], got {:?}.", channels, input.shape),
+        };
+        self.input_shape = input.shape.to_vec();
+
+        let output = self.conv.forward(Tensor::new(input.data, vec![batch, time, 1, channels]), training);
+        let shape = vec![output.shape[0], output.shape[1], output.shape[3]];
+        Tensor::new(output.data, shape)
+    }
+
+    fn backward(&mut self, gradient: &Tensor) -> Tensor {
+        let shape = vec![gradient.shape[0], gradient.shape[1], 1, gradient.shape[2]];
+        let input_grad = self.conv.backward(&Tensor::new(gradient.data.to_vec(), shape));
+        Tensor::new(input_grad.data, self.input_shape.to_vec())
+    }
+
+    fn get_params_list(&self) -> Vec<LearnableParams> {
+        self.conv.get_params_list()
+    }
+
+    fn get_grad(&self, param: &LearnableParams) -> &Tensor {
+        self.conv.get_grad(param)
+    }
+
+    fn get_param(&mut self, param: &LearnableParams) -> &mut Tensor {
+        self.conv.get_param(param)
+    }
+
+    fn is_trainable(&self) -> bool {
+        self.trainable
+    }
+
+    fn set_trainable(&mut self, trainable: bool) {
+        self.trainable = trainable;
+    }
+}
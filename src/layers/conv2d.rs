@@ -0,0 +1,271 @@
+use crate::layers::layer::LayerInfo;
+use crate::layers::layer::Layer;
+use crate::layers::layer::LearnableParams;
+use crate::initializers::Initializer;
+use crate::layers::{DataFormat, Padding, sliding_window_size};
+use crate::tensor::Tensor;
+
+/// 2D convolution layer. Inputs are 4D tensors, either
+/// [batch, channels, height, width] (`DataFormat::ChannelsFirst`) or
+/// [batch, height, width, channels] (`DataFormat::ChannelsLast`).
+pub struct Conv2D {
+    seed: u32,
+    // input stored as [batch, channels, height, width]
+    input: Tensor,
+    // kernels: [filters, input_channels, kernel_height, kernel_width]
+    weights: Tensor,
+    biases: Tensor,
+    weights_grad: Tensor,
+    biases_grad: Tensor,
+    stride: (usize, usize),
+    padding: Padding,
+    dilation: (usize, usize),
+    data_format: DataFormat,
+    trainable: bool,
+    output_shape: Vec<usize>,
+}
+
+impl Conv2D {
+    /// Create a convolution with a stride and a dilation of 1, no padding
+    /// and channels first. Use the `with_*` methods to change them.
+    pub fn new(input_channels: usize, filters: usize, kernel_size: (usize, usize), seed: u32) -> Conv2D {
+        let shape = vec![filters, input_channels, kernel_size.0, kernel_size.1];
+        Conv2D {
+            seed,
+            input: Tensor::new(vec![], vec![]),
+            weights: Conv2D::initialize_kernels(Initializer::GlorotUniform, shape, seed),
+            biases: Tensor::zero(vec![1, filters]),
+            weights_grad: Tensor::new(vec![], vec![]),
+            biases_grad: Tensor::new(vec![], vec![]),
+            stride: (1, 1),
+            padding: Padding::Valid,
+            dilation: (1, 1),
+            data_format: DataFormat::ChannelsFirst,
+            trainable: true,
+            output_shape: vec![],
+        }
+    }
+
+    // Each output depends on input_channels * kernel_size values
+    fn initialize_kernels(initializer: Initializer, shape: Vec<usize>, seed: u32) -> Tensor {
+        let receptive_field = shape[2] * shape[3];
+        let (fan_in, fan_out) = (shape[1] * receptive_field, shape[0] * receptive_field);
+        initializer.initialize(shape, fan_in, fan_out, seed)
+    }
+
+    /// Initialize the kernels with `initializer` (Glorot uniform by default, as `Dense`)
+    pub fn with_kernel_initializer(mut self, initializer: Initializer) -> Conv2D {
+        self.weights = Conv2D::initialize_kernels(initializer, self.weights.shape.to_vec(), self.seed);
+        self
+    }
+
+    pub fn with_stride(mut self, stride: (usize, usize)) -> Conv2D {
+        assert!(stride.0 > 0 && stride.1 > 0, "Stride must be strictly positive.");
+        self.stride = stride;
+        self
+    }
+
+    pub fn with_padding(mut self, padding: Padding) -> Conv2D {
+        self.padding = padding;
+        self
+    }
+
+    pub fn with_dilation(mut self, dilation: (usize, usize)) -> Conv2D {
+        assert!(dilation.0 > 0 && dilation.1 > 0, "Dilation must be strictly positive.");
+        self.dilation = dilation;
+        self
+    }
+
+    pub fn with_data_format(mut self, data_format: DataFormat) -> Conv2D {
+        self.data_format = data_format;
+        self
+    }
+
+    /// Get the output shape (without the batch dimension) for
+    /// an input of shape `input_shape` (without the batch dimension)
+    pub fn compute_output_shape(&self, input_shape: &[usize]) -> Vec<usize> {
+        let (height, width) = match self.data_format {
+            DataFormat::ChannelsFirst => (input_shape[1], input_shape[2]),
+            DataFormat::ChannelsLast => (input_shape[0], input_shape[1]),
+        };
+        let (out_height, _) = self.output_size(height, 0);
+        let (out_width, _) = self.output_size(width, 1);
+        let filters = self.weights.shape[0];
+
+        match self.data_format {
+            DataFormat::ChannelsFirst => vec![filters, out_height, out_width],
+            DataFormat::ChannelsLast => vec![out_height, out_width, filters],
+        }
+    }
+
+    // Return the output size and the padding added before the first
+    // element for an input of `size` along the spatial `axis` (0 = height, 1 = width)
+    fn output_size(&self, size: usize, axis: usize) -> (usize, usize) {
+        self.try_output_size(size, axis).unwrap_or_else(|error| panic!("Conv2D: {}.", error))
+    }
+
+    fn try_output_size(&self, size: usize, axis: usize) -> Result<(usize, usize), String> {
+        let kernel = self.weights.shape[2 + axis];
+        let (stride, dilation) = if axis == 0 {
+            (self.stride.0, self.dilation.0)
+        } else {
+            (self.stride.1, self.dilation.1)
+        };
+        sliding_window_size(size, kernel, stride, dilation, self.padding, axis)
+    }
+
+    // Iterate over every (output position, input position, kernel position) used by the
+    // convolution and call `f(out_index, in_index, kernel_index)` with flat indices
+    fn for_each_connection<F: FnMut(usize, usize, usize)>(&self, mut f: F) {
+        let (batch, channels, height, width) = (self.input.shape[0], self.input.shape[1], self.input.shape[2], self.input.shape[3]);
+        let (filters, kernel_h, kernel_w) = (self.weights.shape[0], self.weights.shape[2], self.weights.shape[3]);
+        let (out_h, pad_top) = self.output_size(height, 0);
+        let (out_w, pad_left) = self.output_size(width, 1);
+
+        for n in 0..batch {
+            for filter in 0..filters {
+                for oh in 0..out_h {
+                    for ow in 0..out_w {
+                        let out_index = ((n * filters + filter) * out_h + oh) * out_w + ow;
+                        for c in 0..channels {
+                            for i in 0..kernel_h {
+                                // position in the input, skipped if inside the padding
+                                let ih = (oh * self.stride.0 + i * self.dilation.0) as isize - pad_top as isize;
+                                if ih < 0 || ih >= height as isize {
+                                    continue;
+                                }
+                                for j in 0..kernel_w {
+                                    let iw = (ow * self.stride.1 + j * self.dilation.1) as isize - pad_left as isize;
+                                    if iw < 0 || iw >= width as isize {
+                                        continue;
+                                    }
+                                    let in_index = ((n * channels + c) * height + ih as usize) * width + iw as usize;
+                                    let kernel_index = ((filter * channels + c) * kernel_h + i) * kernel_w + j;
+                                    f(out_index, in_index, kernel_index);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Layer for Conv2D {
+    fn get_info(&self) -> LayerInfo {
+        LayerInfo {
+            layer_type: format!("Conv2D {}x{}", self.weights.shape[2], self.weights.shape[3]),
+            output_shape: self.output_shape.to_vec(),
+            trainable_param: self.weights.data.len() + self.biases.data.len(),
+            non_trainable_param: 0,
+        }
+    }
+
+    fn build(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, String> {
+        let (channels, height, width) = match (self.data_format, input_shape) {
+            (DataFormat::ChannelsFirst, [channels, height, width]) => (*channels, *height, *width),
+            (DataFormat::ChannelsLast, [height, width, channels]) => (*channels, *height, *width),
+            _ => return Err(format!("expected a 3D input shape ({:?}), got {:?}", self.data_format, input_shape)),
+        };
+        if channels != self.weights.shape[1] {
+            return Err(format!("expected {} input channels, got {}", self.weights.shape[1], channels));
+        }
+        self.try_output_size(height, 0)?;
+        self.try_output_size(width, 1)?;
+
+        self.output_shape = self.compute_output_shape(input_shape);
+        Ok(self.output_shape.to_vec())
+    }
+
+    fn forward(&mut self, input: Tensor, _training: bool) -> Tensor {
+        assert_eq!(input.shape.len(), 4, "Conv2D expects 4D inputs, got shape {:?}.", input.shape);
+
+        self.input = match self.data_format {
+            DataFormat::ChannelsFirst => input,
+            DataFormat::ChannelsLast => input.get_permuted(&[0, 3, 1, 2]),
+        };
+        assert_eq!(self.input.shape[1], self.weights.shape[1], "Wrong number of input channels for Conv2D.");
+
+        let batch = self.input.shape[0];
+        let filters = self.weights.shape[0];
+        let (out_h, _) = self.output_size(self.input.shape[2], 0);
+        let (out_w, _) = self.output_size(self.input.shape[3], 1);
+
+        // start from the biases then add the contribution of every kernel weight
+        let mut data = Vec::with_capacity(batch * filters * out_h * out_w);
+        for _ in 0..batch {
+            for filter in 0..filters {
+                data.extend(vec![self.biases.data[filter]; out_h * out_w]);
+            }
+        }
+        self.for_each_connection(|out_index, in_index, kernel_index| {
+            data[out_index] += self.input.data[in_index] * self.weights.data[kernel_index];
+        });
+
+        let output = Tensor::new(data, vec![batch, filters, out_h, out_w]);
+        match self.data_format {
+            DataFormat::ChannelsFirst => output,
+            DataFormat::ChannelsLast => output.get_permuted(&[0, 2, 3, 1]),
+        }
+    }
+
+    fn backward(&mut self, gradient: &Tensor) -> Tensor {
+        let gradient = match self.data_format {
+            DataFormat::ChannelsFirst => gradient.clone(),
+            DataFormat::ChannelsLast => gradient.get_permuted(&[0, 3, 1, 2]),
+        };
+
+        let mut weights_grad = vec![0.0; self.weights.data.len()];
+        let mut input_grad = vec![0.0; self.input.data.len()];
+        self.for_each_connection(|out_index, in_index, kernel_index| {
+            weights_grad[kernel_index] += gradient.data[out_index] * self.input.data[in_index];
+            input_grad[in_index] += gradient.data[out_index] * self.weights.data[kernel_index];
+        });
+
+        // bias gradient is the sum of the gradient over batch and spatial positions
+        let filters = gradient.shape[1];
+        let positions = gradient.shape[2] * gradient.shape[3];
+        let mut biases_grad = vec![0.0; filters];
+        for (i, g) in gradient.data.iter().enumerate() {
+            biases_grad[(i / positions) % filters] += g;
+        }
+
+        self.weights_grad = Tensor::new(weights_grad, self.weights.shape.to_vec());
+        self.biases_grad = Tensor::new(biases_grad, self.biases.shape.to_vec());
+
+        let input_grad = Tensor::new(input_grad, self.input.shape.to_vec());
+        match self.data_format {
+            DataFormat::ChannelsFirst => input_grad,
+            DataFormat::ChannelsLast => input_grad.get_permuted(&[0, 2, 3, 1]),
+        }
+    }
+
+    fn get_params_list(&self) -> Vec<LearnableParams> {
+        vec![LearnableParams::Weights, LearnableParams::Biases]
+    }
+
+    fn get_grad(&self, param: &LearnableParams) -> &Tensor {
+        match param {
+            LearnableParams::Weights => &self.weights_grad,
+            LearnableParams::Biases => &self.biases_grad,
+            _ => panic!("Conv2D does not have {:?} parameters.", param),
+        }
+    }
+
+    fn get_param(&mut self, param: &LearnableParams) -> &mut Tensor {
+        match param {
+            LearnableParams::Weights => &mut self.weights,
+            LearnableParams::Biases => &mut self.biases,
+            _ => panic!("Conv2D does not have {:?} parameters.", param),
+        }
+    }
+
+    fn is_trainable(&self) -> bool {
+        self.trainable
+    }
+
+    fn set_trainable(&mut self, trainable: bool) {
+        self.trainable = trainable;
+    }
+}
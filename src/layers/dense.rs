@@ -2,41 +2,97 @@ use crate::layers::layer::LayerInfo;
 use crate::layers::layer::Layer;
 use crate::tensor::Tensor;
 use crate::layers::layer::LearnableParams;
+use crate::initializers::Initializer;
+use crate::random::SeedSequence;
+use crate::regularizers::Regularizer;
 
 pub struct Dense {
+    seed: u32,
+    input_units: usize,
+    kernel_initializer: Initializer,
+    bias_initializer: Initializer,
     input: Tensor,
     weights: Tensor,
     biases: Tensor,
     weights_grad: Tensor,
-    biases_grad: Tensor
+    biases_grad: Tensor,
+    kernel_regularizer: Option<Regularizer>,
+    bias_regularizer: Option<Regularizer>,
+    trainable: bool,
+    output_shape: Vec<usize>
 }
 
 impl Dense {
+    /// Weights use the Glorot uniform initializer and biases are zeros,
+    /// they are drawn when the layer is built
     pub fn new(input_units: usize, output_units: usize, seed: u32) -> Dense {
-        // initialize with random values following special normal distribution
-        // allowing theoritical faster convergence (Xavier Initialization)
-        let variance_w = 2.0 / (input_units + output_units) as f64;
-        let variance_b = 2.0 / (output_units) as f64;
         Dense {
+            seed,
+            input_units,
+            kernel_initializer: Initializer::GlorotUniform,
+            bias_initializer: Initializer::Zeros,
             input: Tensor::new(vec![], vec![]),
-            weights: Tensor::random_normal(vec![input_units, output_units], 0.0, variance_w, seed),
-            biases: Tensor::random_normal(vec![1, output_units], 1.0, variance_b, seed),
+            weights: Tensor::new(vec![], vec![]),
+            biases: Tensor::new(vec![], vec![]),
             weights_grad: Tensor::new(vec![], vec![]),
-            biases_grad: Tensor::new(vec![], vec![])
+            biases_grad: Tensor::new(vec![], vec![]),
+            kernel_regularizer: None,
+            bias_regularizer: None,
+            trainable: true,
+            output_shape: vec![output_units]
         }
     }
+
+    /// Initialize the weights with `initializer`
+    pub fn with_kernel_initializer(mut self, initializer: Initializer) -> Dense {
+        self.kernel_initializer = initializer;
+        self
+    }
+
+    /// Initialize the biases with `initializer`
+    pub fn with_bias_initializer(mut self, initializer: Initializer) -> Dense {
+        self.bias_initializer = initializer;
+        self
+    }
+
+    /// Penalize the weights with `regularizer`
+    pub fn with_kernel_regularizer(mut self, regularizer: Regularizer) -> Dense {
+        self.kernel_regularizer = Some(regularizer);
+        self
+    }
+
+    /// Penalize the biases with `regularizer`
+    pub fn with_bias_regularizer(mut self, regularizer: Regularizer) -> Dense {
+        self.bias_regularizer = Some(regularizer);
+        self
+    }
 }
 
 impl Layer for Dense {
     fn get_info(&self) -> LayerInfo {
         LayerInfo {
             layer_type: format!("Dense"),
-            output_shape: self.weights.shape.to_vec(),
-            trainable_param: self.weights.shape.iter().product(),
+            output_shape: self.output_shape.to_vec(),
+            trainable_param: (self.input_units + 1) * self.output_shape[0],
             non_trainable_param: 0,
         }
     }
 
+    fn build(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, String> {
+        let (input_units, output_units) = (self.input_units, self.output_shape[0]);
+        if input_shape != [input_units] {
+            return Err(format!("expected an input shape [{}], got {:?}", input_units, input_shape));
+        }
+        // building the layer again keeps the parameters drawn the first time
+        if self.weights.shape.is_empty() {
+            let seeds = SeedSequence::new(self.seed);
+            self.weights = self.kernel_initializer.initialize(vec![input_units, output_units], input_units, output_units, seeds.child(0).seed());
+            // another stream so the biases do not repeat the weights
+            self.biases = self.bias_initializer.initialize(vec![1, output_units], input_units, output_units, seeds.child(1).seed());
+        }
+        Ok(self.output_shape.to_vec())
+    }
+
     fn forward(&mut self, input: Tensor, _training: bool) -> Tensor {
         // Perform an affine transformation:
         // f(x) = <W*x> + b
@@ -56,6 +112,12 @@ impl Layer for Dense {
         // panic!("input.T {:?}  grad {:?}", &self.input.get_transpose().shape, gradient.shape);
         self.weights_grad = &self.input.get_transpose() * gradient;
         self.biases_grad = gradient.get_sum(0);
+        if let Some(regularizer) = &self.kernel_regularizer {
+            self.weights_grad = &self.weights_grad + &regularizer.gradient(&self.weights);
+        }
+        if let Some(regularizer) = &self.bias_regularizer {
+            self.biases_grad = &self.biases_grad + &regularizer.gradient(&self.biases);
+        }
         
         assert_eq!(self.weights_grad.shape, self.weights.shape, "Wrong shape for weight gradients.");
         assert_eq!(self.biases_grad.shape, self.biases.shape, "Wrong shape for biases gradients.");
@@ -76,6 +138,7 @@ impl Layer for Dense {
             LearnableParams::Biases => {
                 &self.biases_grad
             }
+            _ => panic!("Dense does not have {:?} parameters.", param)
         }
     }
 
@@ -87,7 +150,20 @@ impl Layer for Dense {
             LearnableParams::Biases => {
                 &mut self.biases
             }
+            _ => panic!("Dense does not have {:?} parameters.", param)
         }
     }
 
+    fn get_regularization_loss(&self) -> f64 {
+        self.kernel_regularizer.map_or(0.0, |r| r.penalty(&self.weights))
+            + self.bias_regularizer.map_or(0.0, |r| r.penalty(&self.biases))
+    }
+
+    fn is_trainable(&self) -> bool {
+        self.trainable
+    }
+
+    fn set_trainable(&mut self, trainable: bool) {
+        self.trainable = trainable;
+    }
 }
@@ -2,14 +2,17 @@ use crate::layers::layer::LayerInfo;
 use crate::layers::layer::Layer;
 use crate::tensor::Tensor;
 use crate::layers::layer::LearnableParams;
+use crate::random::SeedSequence;
 
 pub struct Dropout {
     input: Tensor,
     prob: f64,
-    // Store the seed so the Dropout struct can increment it
-    // to generate new masks at each forward pass
-    seed: u32, 
-    mask: Tensor
+    // Masks of the successive forward passes are drawn from the
+    // children of the seed sequence of the layer
+    seed: u32,
+    masks: u64,
+    mask: Tensor,
+    output_shape: Vec<usize>
 }
 
 impl Dropout {
@@ -26,7 +29,9 @@ impl Dropout {
             input: Tensor::new(vec![], vec![]),
             prob,
             seed,
-            mask: Tensor::new(vec![], vec![])
+            masks: 0,
+            mask: Tensor::new(vec![], vec![]),
+            output_shape: vec![]
         }
     }
 }
@@ -35,12 +40,17 @@ impl Layer for Dropout {
     fn get_info(&self) -> LayerInfo {
         LayerInfo {
             layer_type: format!("Dropout {:.2}%", self.prob*100.0),
-            output_shape: vec![],
+            output_shape: self.output_shape.to_vec(),
             trainable_param: 0,
             non_trainable_param: 0,
         }
     }
 
+    fn build(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, String> {
+        self.output_shape = input_shape.to_vec();
+        Ok(self.output_shape.to_vec())
+    }
+
     fn forward(&mut self, input: Tensor, training: bool) -> Tensor {
         // We don't use dropout for inference (training = false)
         if training == false {
@@ -50,8 +60,9 @@ impl Layer for Dropout {
 
         // Generate a random mask at each forward pass
         // We use inverted dropout instead of the classic one here
-        self.seed += 1;
-        self.mask = Tensor::mask(&input.shape, self.prob, self.seed);
+        let seed = SeedSequence::new(self.seed).child(self.masks).seed();
+        self.masks += 1;
+        self.mask = Tensor::mask(&input.shape, self.prob, seed);
         let masked_output = input.mult_el(&self.mask);
         self.input = input;
 
@@ -0,0 +1,139 @@
+use crate::layers::layer::LayerInfo;
+use crate::layers::layer::Layer;
+use crate::layers::layer::LearnableParams;
+use crate::initializers::Initializer;
+use crate::tensor::Tensor;
+
+/// Embedding layer: each input value is an integer index in
+/// `0..vocab_size` replaced by a trainable vector of size `dim`.
+/// Inputs of shape [batch, sequence] give outputs of shape [batch, sequence, dim].
+///
+/// The gradient is sparse: only the rows of the vectors looked up during the
+/// forward pass are stored (see `get_grad_rows`) and updated by the optimizer.
+pub struct Embedding {
+    seed: u32,
+    indices: Vec<usize>,
+    input_shape: Vec<usize>,
+    // vectors: [vocab_size, dim]
+    weights: Tensor,
+    // gradient of the rows `grad_rows`: [grad_rows.len(), dim]
+    weights_grad: Tensor,
+    grad_rows: Vec<usize>,
+    trainable: bool,
+    output_shape: Vec<usize>,
+}
+
+impl Embedding {
+    pub fn new(vocab_size: usize, dim: usize, seed: u32) -> Embedding {
+        Embedding {
+            seed,
+            indices: vec![],
+            input_shape: vec![],
+            weights: Tensor::random_normal(vec![vocab_size, dim], 0.0, 0.05, seed),
+            weights_grad: Tensor::new(vec![], vec![]),
+            grad_rows: vec![],
+            trainable: true,
+            output_shape: vec![],
+        }
+    }
+
+    /// Initialize the vectors with `initializer` (normal with a standard
+    /// deviation of 0.05 by default)
+    pub fn with_embeddings_initializer(mut self, initializer: Initializer) -> Embedding {
+        let (vocab_size, dim) = (self.weights.shape[0], self.weights.shape[1]);
+        self.weights = initializer.initialize(vec![vocab_size, dim], vocab_size, dim, self.seed);
+        self
+    }
+}
+
+impl Layer for Embedding {
+    fn get_info(&self) -> LayerInfo {
+        LayerInfo {
+            layer_type: format!("Embedding {}", self.weights.shape[0]),
+            output_shape: self.output_shape.to_vec(),
+            trainable_param: self.weights.data.len(),
+            non_trainable_param: 0,
+        }
+    }
+
+    fn build(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, String> {
+        self.output_shape = input_shape.to_vec();
+        self.output_shape.push(self.weights.shape[1]);
+        Ok(self.output_shape.to_vec())
+    }
+
+    fn forward(&mut self, input: Tensor, _training: bool) -> Tensor {
+        let (vocab_size, dim) = (self.weights.shape[0], self.weights.shape[1]);
+
+        self.indices = input.data.iter().map(|value| {
+            assert!(value.fract() == 0.0 && *value >= 0.0 && (*value as usize) < vocab_size,
+                "Embedding expects integer indices between 0 and {}, got {}.", vocab_size - 1, value);
+            *value as usize
+        }).collect();
+
+        let mut data = Vec::with_capacity(self.indices.len() * dim);
+        for index in &self.indices {
+            data.extend_from_slice(&self.weights.data[index * dim..(index + 1) * dim]);
+        }
+
+        self.input_shape = input.shape;
+        let mut shape = self.input_shape.to_vec();
+        shape.push(dim);
+        Tensor::new(data, shape)
+    }
+
+    fn backward(&mut self, gradient: &Tensor) -> Tensor {
+        let dim = self.weights.shape[1];
+
+        // accumulate the gradient of each distinct index in a compact tensor
+        let mut rows = self.indices.to_vec();
+        rows.sort_unstable();
+        rows.dedup();
+
+        let mut grad = vec![0.0; rows.len() * dim];
+        for (i, index) in self.indices.iter().enumerate() {
+            let k = rows.binary_search(index).unwrap();
+            for j in 0..dim {
+                grad[k * dim + j] += gradient.data[i * dim + j];
+            }
+        }
+        self.weights_grad = Tensor::new(grad, vec![rows.len(), dim]);
+        self.grad_rows = rows;
+
+        // indices are not differentiable
+        Tensor::zero(self.input_shape.to_vec())
+    }
+
+    fn get_params_list(&self) -> Vec<LearnableParams> {
+        vec![LearnableParams::Weights]
+    }
+
+    fn get_grad(&self, param: &LearnableParams) -> &Tensor {
+        match param {
+            LearnableParams::Weights => &self.weights_grad,
+            _ => panic!("Embedding does not have {:?} parameters.", param),
+        }
+    }
+
+    fn get_grad_rows(&self, param: &LearnableParams) -> Option<Vec<usize>> {
+        match param {
+            LearnableParams::Weights => Some(self.grad_rows.to_vec()),
+            _ => None,
+        }
+    }
+
+    fn get_param(&mut self, param: &LearnableParams) -> &mut Tensor {
+        match param {
+            LearnableParams::Weights => &mut self.weights,
+            _ => panic!("Embedding does not have {:?} parameters.", param),
+        }
+    }
+
+    fn is_trainable(&self) -> bool {
+        self.trainable
+    }
+
+    fn set_trainable(&mut self, trainable: bool) {
+        self.trainable = trainable;
+    }
+}
@@ -0,0 +1,65 @@
+use crate::layers::layer::LayerInfo;
+use crate::layers::layer::Layer;
+use crate::tensor::Tensor;
+use crate::layers::layer::LearnableParams;
+
+/// Flatten all the dimensions except the batch one:
+/// [batch, d1, d2, ...] becomes [batch, d1 * d2 * ...]
+pub struct Flatten {
+    input_shape: Vec<usize>,
+    output_shape: Vec<usize>,
+}
+
+impl Flatten {
+    pub fn new() -> Flatten {
+        Flatten {
+            input_shape: vec![],
+            output_shape: vec![],
+        }
+    }
+}
+
+impl Default for Flatten {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Layer for Flatten {
+    fn get_info(&self) -> LayerInfo {
+        LayerInfo {
+            layer_type: "Flatten".to_string(),
+            output_shape: self.output_shape.to_vec(),
+            trainable_param: 0,
+            non_trainable_param: 0,
+        }
+    }
+
+    fn build(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, String> {
+        self.output_shape = vec![input_shape.iter().product()];
+        Ok(self.output_shape.to_vec())
+    }
+
+    fn forward(&mut self, input: Tensor, _training: bool) -> Tensor {
+        let batch = input.shape[0];
+        let size = input.data.len() / batch;
+        self.input_shape = input.shape.to_vec();
+        Tensor::new(input.data, vec![batch, size])
+    }
+
+    fn backward(&mut self, gradient: &Tensor) -> Tensor {
+        Tensor::new(gradient.data.to_vec(), self.input_shape.to_vec())
+    }
+
+    fn get_params_list(&self) -> Vec<LearnableParams> {
+        vec![]
+    }
+
+    fn get_grad(&self, _param: &LearnableParams) -> &Tensor {
+        panic!("Layer does not have learnable parameters.")
+    }
+
+    fn get_param(&mut self, _param: &LearnableParams) -> &mut Tensor {
+        panic!("Layer does not have learnable parameters.")
+    }
+}
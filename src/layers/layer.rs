@@ -1,9 +1,22 @@
 use crate::tensor::Tensor;
 use std::fmt;
 
+#[derive(Clone, Debug, PartialEq)]
 pub enum LearnableParams {
     Weights,
-    Biases
+    Biases,
+    // kernel applied to the previous state of recurrent layers
+    RecurrentWeights,
+    // scale and shift of normalization layers
+    Gamma,
+    Beta,
+    // negative slopes of PReLU layers
+    Alpha,
+    // statistics of normalization layers, not updated by optimizers
+    MovingMean,
+    MovingVariance,
+    // parameter of the sublayer at the given index of a composite layer
+    Sublayer(usize, Box<LearnableParams>),
 }
 
 #[derive(Debug)]
@@ -14,13 +27,39 @@ pub struct LayerInfo {
     pub non_trainable_param: usize,
 }
 
+/// Implement this trait to create custom layers and add them to a model with
+/// `Sequential::add_layer` or `LayerEnum::Custom`.
 pub trait Layer {
     fn forward(&mut self, input: Tensor, training: bool) -> Tensor;
     fn backward(&mut self, gradient: &Tensor) -> Tensor;
     fn get_info(&self) -> LayerInfo;
+    /// Check the shape of the inputs (without the batch dimension) and
+    /// return the shape of the outputs. Called by `Sequential::compile`.
+    fn build(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, String>;
     fn get_params_list(&self) -> Vec<LearnableParams>;
     fn get_param(&mut self, param: &LearnableParams) -> &mut Tensor;
     fn get_grad(&self, param: &LearnableParams) -> &Tensor;
+    /// Sparse gradients: indices of the rows of `param` stored by `get_grad`,
+    /// in the same order. `None` when the gradient has the shape of `param`.
+    fn get_grad_rows(&self, _param: &LearnableParams) -> Option<Vec<usize>> {
+        None
+    }
+    /// Penalty of the regularizers of the layer (computed with its current
+    /// parameters and the inputs of the last forward pass), added to the
+    /// loss reported by `fit`. Its gradients are included in `get_grad`
+    /// and `backward`.
+    fn get_regularization_loss(&self) -> f64 {
+        0.0
+    }
+    /// Whether optimizers update the parameters of the layer. A frozen layer
+    /// still propagates the gradients to the previous layers.
+    fn is_trainable(&self) -> bool {
+        true
+    }
+    /// Freeze (`false`) or unfreeze the parameters of the layer. Layers
+    /// supporting it override this method and `is_trainable`: by default it
+    /// does nothing and `Sequential::set_trainable` reports an error.
+    fn set_trainable(&mut self, _trainable: bool) {}
 }
 
 impl fmt::Debug for dyn Layer {
@@ -0,0 +1,177 @@
+use crate::layers::layer::LayerInfo;
+use crate::layers::layer::Layer;
+use crate::layers::layer::LearnableParams;
+use crate::tensor::Tensor;
+
+/// Statistics used by a `LayerNorm` layer
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NormMode {
+    /// x_hat = (x - mean) / sqrt(variance + epsilon)
+    Layer,
+    /// x_hat = x / sqrt(mean(x^2) + epsilon)
+    RMS,
+}
+
+/// Normalization of each sample over the features (last axis), then
+/// scaled by `gamma` and shifted by `beta`. Unlike `BatchNorm`, the
+/// statistics never depend on the other samples of the batch so training
+/// and inference behave the same.
+pub struct LayerNorm {
+    mode: NormMode,
+    epsilon: f64,
+    gamma: Tensor,
+    beta: Tensor,
+    gamma_grad: Tensor,
+    beta_grad: Tensor,
+    // normalized input and divisor of each sample saved during the forward pass
+    normalized: Tensor,
+    stdev: Vec<f64>,
+    trainable: bool,
+    output_shape: Vec<usize>,
+}
+
+impl LayerNorm {
+    pub fn new(mode: NormMode, epsilon: f64) -> LayerNorm {
+        LayerNorm {
+            mode,
+            epsilon,
+            gamma: Tensor::new(vec![], vec![]),
+            beta: Tensor::new(vec![], vec![]),
+            gamma_grad: Tensor::new(vec![], vec![]),
+            beta_grad: Tensor::new(vec![], vec![]),
+            normalized: Tensor::new(vec![], vec![]),
+            stdev: vec![],
+            trainable: true,
+            output_shape: vec![],
+        }
+    }
+
+    // Create the parameters the first time the number of features is known
+    fn init_params(&mut self, features: usize) {
+        if self.gamma.data.len() != features {
+            self.gamma = Tensor::one(vec![1, features]);
+            self.beta = Tensor::zero(vec![1, features]);
+        }
+    }
+}
+
+impl Layer for LayerNorm {
+    fn get_info(&self) -> LayerInfo {
+        let name = match self.mode {
+            NormMode::Layer => "LayerNorm",
+            NormMode::RMS => "RMSNorm",
+        };
+        LayerInfo {
+            layer_type: name.to_string(),
+            output_shape: self.output_shape.to_vec(),
+            trainable_param: self.gamma.data.len() + self.beta.data.len(),
+            non_trainable_param: 0,
+        }
+    }
+
+    fn build(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, String> {
+        match input_shape.last() {
+            Some(features) => self.init_params(*features),
+            None => return Err("expected at least a 1D input shape".to_string()),
+        }
+        self.output_shape = input_shape.to_vec();
+        Ok(self.output_shape.to_vec())
+    }
+
+    fn forward(&mut self, input: Tensor, _training: bool) -> Tensor {
+        assert!(input.shape.len() >= 2, "{} expects at least 2D inputs, got shape {:?}.", self.get_info().layer_type, input.shape);
+        let features = input.shape[input.shape.len() - 1];
+        self.init_params(features);
+
+        let mut normalized = Vec::with_capacity(input.data.len());
+        let mut output = Vec::with_capacity(input.data.len());
+        self.stdev.clear();
+        for row in input.data.chunks(features) {
+            let (mean, stdev) = match self.mode {
+                NormMode::Layer => {
+                    let mean = row.iter().sum::<f64>() / features as f64;
+                    let variance = row.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / features as f64;
+                    (mean, (variance + self.epsilon).sqrt())
+                }
+                NormMode::RMS => {
+                    let mean_square = row.iter().map(|x| x * x).sum::<f64>() / features as f64;
+                    (0.0, (mean_square + self.epsilon).sqrt())
+                }
+            };
+            self.stdev.push(stdev);
+
+            for (j, x) in row.iter().enumerate() {
+                let x_hat = (x - mean) / stdev;
+                normalized.push(x_hat);
+                output.push(self.gamma.data[j] * x_hat + self.beta.data[j]);
+            }
+        }
+        self.normalized = Tensor::new(normalized, input.shape.to_vec());
+
+        Tensor::new(output, input.shape)
+    }
+
+    fn backward(&mut self, gradient: &Tensor) -> Tensor {
+        let features = self.gamma.data.len();
+        let count = features as f64;
+
+        let mut gamma_grad = vec![0.0; features];
+        let mut beta_grad = vec![0.0; features];
+        let mut input_grad = Vec::with_capacity(gradient.data.len());
+
+        let rows = gradient.data.chunks(features).zip(self.normalized.data.chunks(features));
+        for ((grad_row, x_hat_row), stdev) in rows.zip(&self.stdev) {
+            // gradient with respect to the normalized input
+            let mut x_hat_grad = vec![0.0; features];
+            for j in 0..features {
+                gamma_grad[j] += grad_row[j] * x_hat_row[j];
+                beta_grad[j] += grad_row[j];
+                x_hat_grad[j] = grad_row[j] * self.gamma.data[j];
+            }
+
+            let sum: f64 = x_hat_grad.iter().sum();
+            let dot: f64 = x_hat_grad.iter().zip(x_hat_row).map(|(g, x)| g * x).sum();
+            for j in 0..features {
+                input_grad.push(match self.mode {
+                    // dx = (N * dx_hat - sum(dx_hat) - x_hat * sum(dx_hat * x_hat)) / (N * stdev)
+                    NormMode::Layer => (count * x_hat_grad[j] - sum - x_hat_row[j] * dot) / (count * stdev),
+                    // dx = (dx_hat - x_hat * mean(dx_hat * x_hat)) / rms
+                    NormMode::RMS => (x_hat_grad[j] - x_hat_row[j] * dot / count) / stdev,
+                });
+            }
+        }
+
+        self.gamma_grad = Tensor::new(gamma_grad, self.gamma.shape.to_vec());
+        self.beta_grad = Tensor::new(beta_grad, self.beta.shape.to_vec());
+
+        Tensor::new(input_grad, gradient.shape.to_vec())
+    }
+
+    fn get_params_list(&self) -> Vec<LearnableParams> {
+        vec![LearnableParams::Gamma, LearnableParams::Beta]
+    }
+
+    fn get_grad(&self, param: &LearnableParams) -> &Tensor {
+        match param {
+            LearnableParams::Gamma => &self.gamma_grad,
+            LearnableParams::Beta => &self.beta_grad,
+            _ => panic!("{} does not have {:?} parameters.", self.get_info().layer_type, param),
+        }
+    }
+
+    fn get_param(&mut self, param: &LearnableParams) -> &mut Tensor {
+        match param {
+            LearnableParams::Gamma => &mut self.gamma,
+            LearnableParams::Beta => &mut self.beta,
+            _ => panic!("{} does not have {:?} parameters.", self.get_info().layer_type, param),
+        }
+    }
+
+    fn is_trainable(&self) -> bool {
+        self.trainable
+    }
+
+    fn set_trainable(&mut self, trainable: bool) {
+        self.trainable = trainable;
+    }
+}
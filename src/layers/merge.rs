@@ -0,0 +1,112 @@
+use crate::tensor::Tensor;
+
+/// Operation used by a `Merge` layer to combine its inputs
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MergeOp {
+    /// Element-wise sum of inputs having the same shape
+    Add,
+    /// Concatenation along the last axis, the other axes must be equal
+    Concatenate,
+    /// Element-wise product of inputs having the same shape
+    Multiply,
+}
+
+/// Combine the outputs of several layers into one tensor. Unlike the other
+/// layers a `Merge` layer has several inputs so it is only used by `Graph`.
+pub struct Merge {
+    op: MergeOp,
+    inputs: Vec<Tensor>,
+    output_shape: Vec<usize>,
+}
+
+impl Merge {
+    pub fn new(op: MergeOp) -> Merge {
+        Merge {
+            op,
+            inputs: vec![],
+            output_shape: vec![],
+        }
+    }
+
+    pub fn get_op(&self) -> MergeOp {
+        self.op
+    }
+
+    pub fn get_output_shape(&self) -> Vec<usize> {
+        self.output_shape.to_vec()
+    }
+
+    /// Check the input shapes (without the batch dimension) and return the output shape
+    pub fn build(&mut self, input_shapes: &[Vec<usize>]) -> Result<Vec<usize>, String> {
+        if input_shapes.len() < 2 {
+            return Err(format!("expected at least 2 inputs, got {}", input_shapes.len()));
+        }
+        let first = &input_shapes[0];
+        self.output_shape = match self.op {
+            MergeOp::Add | MergeOp::Multiply => {
+                if input_shapes.iter().any(|shape| shape != first) {
+                    return Err("all the inputs must have the same shape".to_string());
+                }
+                first.to_vec()
+            }
+            MergeOp::Concatenate => {
+                let axis = first.len().saturating_sub(1);
+                if first.is_empty() || input_shapes.iter().any(|shape| shape.len() != first.len() || shape[..axis] != first[..axis]) {
+                    return Err("all the inputs must have the same shape except for the last axis".to_string());
+                }
+                let mut shape = first.to_vec();
+                shape[axis] = input_shapes.iter().map(|shape| shape[axis]).sum();
+                shape
+            }
+        };
+        Ok(self.output_shape.to_vec())
+    }
+
+    pub fn forward(&mut self, inputs: Vec<Tensor>) -> Tensor {
+        let output = match self.op {
+            MergeOp::Add => inputs[1..].iter().fold(inputs[0].clone(), |sum, input| sum + input),
+            MergeOp::Multiply => inputs[1..].iter().fold(inputs[0].clone(), |product, input| product.mult_el(input)),
+            MergeOp::Concatenate => {
+                let widths: Vec<usize> = inputs.iter().map(|input| input.shape[input.shape.len() - 1]).collect();
+                let rows = inputs[0].data.len() / widths[0];
+                let mut data = Vec::with_capacity(inputs.iter().map(|input| input.data.len()).sum());
+                for row in 0..rows {
+                    for (input, width) in inputs.iter().zip(&widths) {
+                        data.extend_from_slice(&input.data[row * width..(row + 1) * width]);
+                    }
+                }
+                let mut shape = inputs[0].shape.to_vec();
+                *shape.last_mut().unwrap() = widths.iter().sum();
+                Tensor::new(data, shape)
+            }
+        };
+        self.inputs = inputs;
+        output
+    }
+
+    /// Return the gradient with respect to each input
+    pub fn backward(&mut self, gradient: &Tensor) -> Vec<Tensor> {
+        match self.op {
+            MergeOp::Add => vec![gradient.clone(); self.inputs.len()],
+            MergeOp::Multiply => (0..self.inputs.len()).map(|i| {
+                // product of the other inputs
+                self.inputs.iter().enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .fold(gradient.clone(), |grad, (_, input)| grad.mult_el(input))
+            }).collect(),
+            MergeOp::Concatenate => {
+                let widths: Vec<usize> = self.inputs.iter().map(|input| input.shape[input.shape.len() - 1]).collect();
+                let total: usize = widths.iter().sum();
+                let mut grads: Vec<Vec<f64>> = self.inputs.iter().map(|input| Vec::with_capacity(input.data.len())).collect();
+                for row in gradient.data.chunks(total) {
+                    let mut start = 0;
+                    for (grad, width) in grads.iter_mut().zip(&widths) {
+                        grad.extend_from_slice(&row[start..start + width]);
+                        start += width;
+                    }
+                }
+                grads.into_iter().zip(&self.inputs).map(|(grad, input)| Tensor::new(grad, input.shape.to_vec())).collect()
+            }
+        }
+    }
+}
@@ -5,13 +5,185 @@ pub mod dense;
 pub mod softmax;
 pub mod sigmoid;
 pub mod dropout;
+pub mod conv2d;
+pub mod pooling2d;
+pub mod conv1d;
+pub mod pooling1d;
+pub mod flatten;
+pub mod reshape;
+pub mod batch_norm;
+pub mod layer_norm;
+pub mod embedding;
+pub mod recurrent;
+pub mod bidirectional;
+pub mod attention;
+pub mod transformer;
+pub mod merge;
+pub mod activation;
+pub mod prelu;
+pub mod activity_regularization;
+
+use crate::initializers::Initializer;
+use crate::regularizers::Regularizer;
+use crate::tensor::Tensor;
 
 pub enum LayerEnum {
-    Dense {input_units: usize, output_units: usize},
+    /// `input_units` is inferred from the previous layer when `None`.
+    /// The usual initializers are `GlorotUniform` (or `HeUniform` before a
+    /// ReLU) for the kernel and `Zeros` for the bias.
+    /// Regularizers are optional penalties of the weights and biases.
+    Dense {
+        input_units: Option<usize>,
+        output_units: usize,
+        kernel_initializer: Initializer,
+        bias_initializer: Initializer,
+        kernel_regularizer: Option<Regularizer>,
+        bias_regularizer: Option<Regularizer>,
+    },
     ReLU,
     Softmax,
     Sigmoid,
     TanH,
-    Dropout {prob: f64}
+    Dropout {prob: f64},
+    LeakyReLU {slope: f64},
+    ELU {alpha: f64},
+    SELU,
+    GELU,
+    Swish {beta: f64},
+    Softplus,
+    Mish,
+    /// Slopes learned for each channel, starting at `initial_slope`
+    PReLU {initial_slope: f64, data_format: DataFormat},
+    /// Penalize the outputs of the previous layer (identity otherwise)
+    ActivityRegularization {regularizer: Regularizer},
+    Conv2D {
+        input_channels: usize,
+        filters: usize,
+        kernel_size: (usize, usize),
+        stride: (usize, usize),
+        padding: Padding,
+        dilation: (usize, usize),
+        data_format: DataFormat,
+    },
+    MaxPool2D {
+        pool_size: (usize, usize),
+        stride: (usize, usize),
+        padding: Padding,
+        data_format: DataFormat,
+    },
+    AvgPool2D {
+        pool_size: (usize, usize),
+        stride: (usize, usize),
+        padding: Padding,
+        data_format: DataFormat,
+    },
+    GlobalMaxPooling2D {data_format: DataFormat},
+    GlobalAveragePooling2D {data_format: DataFormat},
+    Conv1D {
+        input_channels: usize,
+        filters: usize,
+        kernel_size: usize,
+        stride: usize,
+        padding: Padding,
+        dilation: usize,
+    },
+    MaxPool1D {pool_size: usize, stride: usize, padding: Padding},
+    AvgPool1D {pool_size: usize, stride: usize, padding: Padding},
+    Flatten,
+    Reshape {target_shape: Vec<usize>},
+    BatchNorm {momentum: f64, epsilon: f64, data_format: DataFormat},
+    LayerNorm {epsilon: f64},
+    RMSNorm {epsilon: f64},
+    Embedding {vocab_size: usize, dim: usize},
+    /// `input_features` is inferred from the previous layer when `None`.
+    /// `initial_state` replaces the zero initial states, see
+    /// `Recurrent::with_initial_state`.
+    SimpleRNN {input_features: Option<usize>, units: usize, return_sequences: bool, initial_state: Option<Vec<Tensor>>},
+    LSTM {input_features: Option<usize>, units: usize, return_sequences: bool, initial_state: Option<Vec<Tensor>>},
+    GRU {input_features: Option<usize>, units: usize, return_sequences: bool, initial_state: Option<Vec<Tensor>>},
+    /// Wrap a SimpleRNN, LSTM or GRU layer to process the sequence in both directions
+    Bidirectional {layer: Box<LayerEnum>, merge_mode: bidirectional::MergeMode},
+    /// Self-attention over [time, model_dim] inputs, `model_dim` is inferred
+    /// from the previous layer when `None`. `mask_padding` ignores the time
+    /// steps whose input vector is only made of zeros.
+    MultiHeadAttention {model_dim: Option<usize>, heads: usize, causal: bool, mask_padding: bool},
+    /// Attention and feed-forward network with residual connections and
+    /// layer normalization, same options as `MultiHeadAttention`
+    TransformerEncoderBlock {
+        model_dim: Option<usize>,
+        heads: usize,
+        feed_forward_dim: usize,
+        causal: bool,
+        mask_padding: bool,
+    },
+    /// Layer created by a user function, called by `compile` with the seed
+    /// of the model. See `Sequential::add_layer` to add a layer instance.
+    Custom(Box<dyn Fn(u32) -> Box<dyn layer::Layer>>),
+}
+
+impl LayerEnum {
+    /// `Dense` layer with the usual options: Glorot uniform weights, zero
+    /// biases and no regularizers. Use the variant to change them.
+    pub fn dense(input_units: Option<usize>, output_units: usize) -> LayerEnum {
+        LayerEnum::Dense {
+            input_units,
+            output_units,
+            kernel_initializer: Initializer::GlorotUniform,
+            bias_initializer: Initializer::Zeros,
+            kernel_regularizer: None,
+            bias_regularizer: None,
+        }
+    }
 }
 
+/// Padding applied to the spatial dimensions of convolution inputs
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Padding {
+    /// No padding
+    Valid,
+    /// Zero padding so the output size is the input size divided by the stride
+    Same,
+    /// Zero padding of (height, width) elements on each side
+    Explicit(usize, usize),
+    /// Zero padding only before the first element so an output never
+    /// depends on future inputs (for temporal data)
+    Causal,
+}
+
+/// Position of the channels axis in multi-dimensional inputs
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DataFormat {
+    /// [batch, channels, height, width]
+    ChannelsFirst,
+    /// [batch, height, width, channels]
+    ChannelsLast,
+}
+
+// Return the output size and the padding added before the first element
+// when a window of `kernel` elements spaced by `dilation` slides with `stride`
+// over `size` elements. `axis` selects the padding of `Padding::Explicit`
+// (0 = height, 1 = width).
+pub(crate) fn sliding_window_size(size: usize, kernel: usize, stride: usize, dilation: usize, padding: Padding, axis: usize) -> Result<(usize, usize), String> {
+    let effective_kernel = (kernel - 1) * dilation + 1;
+
+    let (padded_size, pad_before) = match padding {
+        Padding::Valid => (size, 0),
+        Padding::Same => {
+            // output size is ceil(size / stride), extra padding goes after
+            let out = size.div_ceil(stride);
+            let pad_total = ((out - 1) * stride + effective_kernel).saturating_sub(size);
+            (size + pad_total, pad_total / 2)
+        }
+        Padding::Explicit(height, width) => {
+            let pad = if axis == 0 { height } else { width };
+            (size + 2 * pad, pad)
+        }
+        Padding::Causal => (size + effective_kernel - 1, effective_kernel - 1),
+    };
+
+    if padded_size < effective_kernel {
+        return Err(format!("window (effective size {}) is larger than the padded input ({})", effective_kernel, padded_size));
+    }
+
+    Ok(((padded_size - effective_kernel) / stride + 1, pad_before))
+}
@@ -0,0 +1,103 @@
+use crate::layers::layer::LayerInfo;
+use crate::layers::layer::Layer;
+use crate::layers::layer::LearnableParams;
+use crate::layers::pooling2d::{Pool2D, PoolMode};
+use crate::layers::{DataFormat, Padding};
+use crate::tensor::Tensor;
+
+/// 1D pooling layer (MaxPool1D or AvgPool1D) over temporal data of
+/// shape [batch, time, channels]. The output shape is [batch, time, channels].
+pub struct Pool1D {
+    pool: Pool2D,
+    mode: PoolMode,
+    pool_size: usize,
+    output_shape: Vec<usize>,
+}
+
+impl Pool1D {
+    /// Create a pooling layer whose stride is the pool size, without padding.
+    pub fn new(mode: PoolMode, pool_size: usize) -> Pool1D {
+        Pool1D {
+            pool: Pool2D::new(mode, (pool_size, 1)).with_data_format(DataFormat::ChannelsLast),
+            mode,
+            pool_size,
+            output_shape: vec![],
+        }
+    }
+
+    pub fn with_stride(mut self, stride: usize) -> Pool1D {
+        self.pool = self.pool.with_stride((stride, 1));
+        self
+    }
+
+    /// Padding is applied to the time axis only (`Padding::Explicit(time, _)`)
+    pub fn with_padding(mut self, padding: Padding) -> Pool1D {
+        let padding = match padding {
+            Padding::Explicit(time, _) => Padding::Explicit(time, 0),
+            padding => padding,
+        };
+        self.pool = self.pool.with_padding(padding);
+        self
+    }
+
+    /// Get the output shape [time, channels] for an input
+    /// of shape [time, channels]
+    pub fn compute_output_shape(&self, input_shape: &[usize]) -> Vec<usize> {
+        let output_shape = self.pool.compute_output_shape(&[input_shape[0], 1, input_shape[1]]);
+        vec![output_shape[0], output_shape[2]]
+    }
+}
+
+impl Layer for Pool1D {
+    fn get_info(&self) -> LayerInfo {
+        let name = match self.mode {
+            PoolMode::Max => "MaxPool1D",
+            PoolMode::Average => "AvgPool1D",
+        };
+        LayerInfo {
+            layer_type: format!("{} {}", name, self.pool_size),
+            output_shape: self.output_shape.to_vec(),
+            trainable_param: 0,
+            non_trainable_param: 0,
+        }
+    }
+
+    fn build(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, String> {
+        let (time, channels) = match input_shape {
+            [time, channels] => (*time, *channels),
+            _ => return Err(format!("expected an input shape [time, channels], got {:?}", input_shape)),
+        };
+
+        let output_shape = self.pool.build(&[time, 1, channels])?;
+        self.output_shape = vec![output_shape[0], output_shape[2]];
+        Ok(self.output_shape.to_vec())
+    }
+
+    fn forward(&mut self, input: Tensor, training: bool) -> Tensor {
+        assert_eq!(input.shape.len(), 3, "Pooling 1D expects inputs of shape [batch, time, channels], got {:?}.", input.shape);
+
+        let shape = vec![input.shape[0], input.shape[1], 1, input.shape[2]];
+        let output = self.pool.forward(Tensor::new(input.data, shape), training);
+        let shape = vec![output.shape[0], output.shape[1], output.shape[3]];
+        Tensor::new(output.data, shape)
+    }
+
+    fn backward(&mut self, gradient: &Tensor) -> Tensor {
+        let shape = vec![gradient.shape[0], gradient.shape[1], 1, gradient.shape[2]];
+        let input_grad = self.pool.backward(&Tensor::new(gradient.data.to_vec(), shape));
+        let shape = vec![input_grad.shape[0], input_grad.shape[1], input_grad.shape[3]];
+        Tensor::new(input_grad.data, shape)
+    }
+
+    fn get_params_list(&self) -> Vec<LearnableParams> {
+        vec![]
+    }
+
+    fn get_grad(&self, _param: &LearnableParams) -> &Tensor {
+        panic!("Layer does not have learnable parameters.")
+    }
+
+    fn get_param(&mut self, _param: &LearnableParams) -> &mut Tensor {
+        panic!("Layer does not have learnable parameters.")
+    }
+}
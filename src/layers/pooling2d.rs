@@ -0,0 +1,360 @@
+use crate::layers::layer::LayerInfo;
+use crate::layers::layer::Layer;
+use crate::layers::layer::LearnableParams;
+use crate::layers::{DataFormat, Padding, sliding_window_size};
+use crate::tensor::Tensor;
+
+/// Reduction applied by a pooling layer over each window
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PoolMode {
+    Max,
+    Average,
+}
+
+/// 2D pooling layer (MaxPool2D or AvgPool2D). Inputs are 4D tensors whose
+/// layout is given by the `DataFormat`. Padded elements are ignored: they are
+/// never selected by max pooling and are not counted by average pooling.
+pub struct Pool2D {
+    mode: PoolMode,
+    pool_size: (usize, usize),
+    stride: (usize, usize),
+    padding: Padding,
+    data_format: DataFormat,
+    // input shape stored as [batch, channels, height, width]
+    input_shape: Vec<usize>,
+    // index of the selected input element for each output (max pooling)
+    argmax: Vec<usize>,
+    output_shape: Vec<usize>,
+}
+
+impl Pool2D {
+    /// Create a pooling layer whose stride is the pool size, without
+    /// padding and with channels first.
+    pub fn new(mode: PoolMode, pool_size: (usize, usize)) -> Pool2D {
+        Pool2D {
+            mode,
+            pool_size,
+            stride: pool_size,
+            padding: Padding::Valid,
+            data_format: DataFormat::ChannelsFirst,
+            input_shape: vec![],
+            argmax: vec![],
+            output_shape: vec![],
+        }
+    }
+
+    pub fn with_stride(mut self, stride: (usize, usize)) -> Pool2D {
+        assert!(stride.0 > 0 && stride.1 > 0, "Stride must be strictly positive.");
+        self.stride = stride;
+        self
+    }
+
+    pub fn with_padding(mut self, padding: Padding) -> Pool2D {
+        self.padding = padding;
+        self
+    }
+
+    pub fn with_data_format(mut self, data_format: DataFormat) -> Pool2D {
+        self.data_format = data_format;
+        self
+    }
+
+    /// Get the output shape (without the batch dimension) for
+    /// an input of shape `input_shape` (without the batch dimension)
+    pub fn compute_output_shape(&self, input_shape: &[usize]) -> Vec<usize> {
+        match self.data_format {
+            DataFormat::ChannelsFirst => {
+                let (out_h, _) = self.output_size(input_shape[1], 0);
+                let (out_w, _) = self.output_size(input_shape[2], 1);
+                vec![input_shape[0], out_h, out_w]
+            }
+            DataFormat::ChannelsLast => {
+                let (out_h, _) = self.output_size(input_shape[0], 0);
+                let (out_w, _) = self.output_size(input_shape[1], 1);
+                vec![out_h, out_w, input_shape[2]]
+            }
+        }
+    }
+
+    fn output_size(&self, size: usize, axis: usize) -> (usize, usize) {
+        self.try_output_size(size, axis).unwrap_or_else(|error| panic!("Pooling 2D: {}.", error))
+    }
+
+    fn try_output_size(&self, size: usize, axis: usize) -> Result<(usize, usize), String> {
+        let (pool, stride) = if axis == 0 {
+            (self.pool_size.0, self.stride.0)
+        } else {
+            (self.pool_size.1, self.stride.1)
+        };
+        // a window could otherwise only cover padded elements, which are ignored
+        if let Padding::Explicit(height, width) = self.padding {
+            let pad = if axis == 0 { height } else { width };
+            if pad > pool / 2 {
+                return Err(format!("explicit padding ({}) must be at most half of the pool size ({})", pad, pool));
+            }
+        }
+        sliding_window_size(size, pool, stride, 1, self.padding, axis)
+    }
+
+    // Call `f(out_index, window)` for each output element where `window`
+    // contains the flat indices of the input elements to reduce
+    fn for_each_window<F: FnMut(usize, &[usize])>(&self, mut f: F) {
+        let (batch, channels, height, width) = (self.input_shape[0], self.input_shape[1], self.input_shape[2], self.input_shape[3]);
+        let (out_h, pad_top) = self.output_size(height, 0);
+        let (out_w, pad_left) = self.output_size(width, 1);
+
+        let mut window = Vec::with_capacity(self.pool_size.0 * self.pool_size.1);
+        for n in 0..batch {
+            for c in 0..channels {
+                for oh in 0..out_h {
+                    for ow in 0..out_w {
+                        window.clear();
+                        for i in 0..self.pool_size.0 {
+                            let ih = (oh * self.stride.0 + i) as isize - pad_top as isize;
+                            if ih < 0 || ih >= height as isize {
+                                continue;
+                            }
+                            for j in 0..self.pool_size.1 {
+                                let iw = (ow * self.stride.1 + j) as isize - pad_left as isize;
+                                if iw < 0 || iw >= width as isize {
+                                    continue;
+                                }
+                                window.push(((n * channels + c) * height + ih as usize) * width + iw as usize);
+                            }
+                        }
+                        f(((n * channels + c) * out_h + oh) * out_w + ow, &window);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Layer for Pool2D {
+    fn get_info(&self) -> LayerInfo {
+        let name = match self.mode {
+            PoolMode::Max => "MaxPool2D",
+            PoolMode::Average => "AvgPool2D",
+        };
+        LayerInfo {
+            layer_type: format!("{} {}x{}", name, self.pool_size.0, self.pool_size.1),
+            output_shape: self.output_shape.to_vec(),
+            trainable_param: 0,
+            non_trainable_param: 0,
+        }
+    }
+
+    fn build(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, String> {
+        let (height, width) = match (self.data_format, input_shape) {
+            (DataFormat::ChannelsFirst, [_, height, width]) => (*height, *width),
+            (DataFormat::ChannelsLast, [height, width, _]) => (*height, *width),
+            _ => return Err(format!("expected a 3D input shape ({:?}), got {:?}", self.data_format, input_shape)),
+        };
+        self.try_output_size(height, 0)?;
+        self.try_output_size(width, 1)?;
+
+        self.output_shape = self.compute_output_shape(input_shape);
+        Ok(self.output_shape.to_vec())
+    }
+
+    fn forward(&mut self, input: Tensor, _training: bool) -> Tensor {
+        assert_eq!(input.shape.len(), 4, "Pooling 2D expects 4D inputs, got shape {:?}.", input.shape);
+
+        let input = match self.data_format {
+            DataFormat::ChannelsFirst => input,
+            DataFormat::ChannelsLast => input.get_permuted(&[0, 3, 1, 2]),
+        };
+        self.input_shape = input.shape.to_vec();
+
+        let (out_h, _) = self.output_size(input.shape[2], 0);
+        let (out_w, _) = self.output_size(input.shape[3], 1);
+        let output_len = input.shape[0] * input.shape[1] * out_h * out_w;
+
+        let mut data = vec![0.0; output_len];
+        let mut argmax = vec![0; output_len];
+        let mode = self.mode;
+        self.for_each_window(|out_index, window| {
+            match mode {
+                PoolMode::Max => {
+                    let mut best = window[0];
+                    for index in window {
+                        if input.data[*index] > input.data[best] {
+                            best = *index;
+                        }
+                    }
+                    argmax[out_index] = best;
+                    data[out_index] = input.data[best];
+                }
+                PoolMode::Average => {
+                    let sum: f64 = window.iter().map(|index| input.data[*index]).sum();
+                    data[out_index] = sum / window.len() as f64;
+                }
+            }
+        });
+        self.argmax = argmax;
+
+        let output = Tensor::new(data, vec![input.shape[0], input.shape[1], out_h, out_w]);
+        match self.data_format {
+            DataFormat::ChannelsFirst => output,
+            DataFormat::ChannelsLast => output.get_permuted(&[0, 2, 3, 1]),
+        }
+    }
+
+    fn backward(&mut self, gradient: &Tensor) -> Tensor {
+        let gradient = match self.data_format {
+            DataFormat::ChannelsFirst => gradient.clone(),
+            DataFormat::ChannelsLast => gradient.get_permuted(&[0, 3, 1, 2]),
+        };
+
+        let mut input_grad = vec![0.0; self.input_shape.iter().product()];
+        match self.mode {
+            // the gradient only flows to the selected element of each window
+            PoolMode::Max => {
+                for (out_index, index) in self.argmax.iter().enumerate() {
+                    input_grad[*index] += gradient.data[out_index];
+                }
+            }
+            // the gradient is spread uniformly over the window
+            PoolMode::Average => {
+                self.for_each_window(|out_index, window| {
+                    for index in window {
+                        input_grad[*index] += gradient.data[out_index] / window.len() as f64;
+                    }
+                });
+            }
+        }
+
+        let input_grad = Tensor::new(input_grad, self.input_shape.to_vec());
+        match self.data_format {
+            DataFormat::ChannelsFirst => input_grad,
+            DataFormat::ChannelsLast => input_grad.get_permuted(&[0, 2, 3, 1]),
+        }
+    }
+
+    fn get_params_list(&self) -> Vec<LearnableParams> {
+        vec![]
+    }
+
+    fn get_grad(&self, _param: &LearnableParams) -> &Tensor {
+        panic!("Layer does not have learnable parameters.")
+    }
+
+    fn get_param(&mut self, _param: &LearnableParams) -> &mut Tensor {
+        panic!("Layer does not have learnable parameters.")
+    }
+}
+
+/// Global pooling layer (GlobalMaxPooling2D or GlobalAveragePooling2D).
+/// Reduce every channel of a 4D input to a single value: the output
+/// shape is [batch, channels].
+pub struct GlobalPool2D {
+    mode: PoolMode,
+    data_format: DataFormat,
+    // input shape stored as [batch, channels, height, width]
+    input_shape: Vec<usize>,
+    argmax: Vec<usize>,
+    output_shape: Vec<usize>,
+}
+
+impl GlobalPool2D {
+    pub fn new(mode: PoolMode, data_format: DataFormat) -> GlobalPool2D {
+        GlobalPool2D {
+            mode,
+            data_format,
+            input_shape: vec![],
+            argmax: vec![],
+            output_shape: vec![],
+        }
+    }
+}
+
+impl Layer for GlobalPool2D {
+    fn get_info(&self) -> LayerInfo {
+        let name = match self.mode {
+            PoolMode::Max => "GlobalMaxPooling2D",
+            PoolMode::Average => "GlobalAveragePooling2D",
+        };
+        LayerInfo {
+            layer_type: name.to_string(),
+            output_shape: self.output_shape.to_vec(),
+            trainable_param: 0,
+            non_trainable_param: 0,
+        }
+    }
+
+    fn build(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, String> {
+        let channels = match (self.data_format, input_shape) {
+            (DataFormat::ChannelsFirst, [channels, _, _]) => *channels,
+            (DataFormat::ChannelsLast, [_, _, channels]) => *channels,
+            _ => return Err(format!("expected a 3D input shape ({:?}), got {:?}", self.data_format, input_shape)),
+        };
+        self.output_shape = vec![channels];
+        Ok(self.output_shape.to_vec())
+    }
+
+    fn forward(&mut self, input: Tensor, _training: bool) -> Tensor {
+        assert_eq!(input.shape.len(), 4, "Global pooling 2D expects 4D inputs, got shape {:?}.", input.shape);
+
+        let input = match self.data_format {
+            DataFormat::ChannelsFirst => input,
+            DataFormat::ChannelsLast => input.get_permuted(&[0, 3, 1, 2]),
+        };
+        self.input_shape = input.shape.to_vec();
+
+        // each (sample, channel) pair is a contiguous block of data
+        let positions = input.shape[2] * input.shape[3];
+        let mut data = Vec::with_capacity(input.shape[0] * input.shape[1]);
+        self.argmax.clear();
+        for (block_index, block) in input.data.chunks(positions).enumerate() {
+            match self.mode {
+                PoolMode::Max => {
+                    let mut best = 0;
+                    for (i, value) in block.iter().enumerate() {
+                        if *value > block[best] {
+                            best = i;
+                        }
+                    }
+                    self.argmax.push(block_index * positions + best);
+                    data.push(block[best]);
+                }
+                PoolMode::Average => data.push(block.iter().sum::<f64>() / positions as f64),
+            }
+        }
+
+        Tensor::new(data, vec![input.shape[0], input.shape[1]])
+    }
+
+    fn backward(&mut self, gradient: &Tensor) -> Tensor {
+        let positions = self.input_shape[2] * self.input_shape[3];
+        let mut input_grad = vec![0.0; self.input_shape.iter().product()];
+
+        for (block_index, g) in gradient.data.iter().enumerate() {
+            match self.mode {
+                PoolMode::Max => input_grad[self.argmax[block_index]] += g,
+                PoolMode::Average => {
+                    for value in &mut input_grad[block_index * positions..(block_index + 1) * positions] {
+                        *value += g / positions as f64;
+                    }
+                }
+            }
+        }
+
+        let input_grad = Tensor::new(input_grad, self.input_shape.to_vec());
+        match self.data_format {
+            DataFormat::ChannelsFirst => input_grad,
+            DataFormat::ChannelsLast => input_grad.get_permuted(&[0, 2, 3, 1]),
+        }
+    }
+
+    fn get_params_list(&self) -> Vec<LearnableParams> {
+        vec![]
+    }
+
+    fn get_grad(&self, _param: &LearnableParams) -> &Tensor {
+        panic!("Layer does not have learnable parameters.")
+    }
+
+    fn get_param(&mut self, _param: &LearnableParams) -> &mut Tensor {
+        panic!("Layer does not have learnable parameters.")
+    }
+}
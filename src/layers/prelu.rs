@@ -0,0 +1,135 @@
+use crate::layers::layer::LayerInfo;
+use crate::layers::layer::Layer;
+use crate::layers::layer::LearnableParams;
+use crate::layers::DataFormat;
+use crate::tensor::Tensor;
+
+/// Parametric ReLU: x if x > 0, alpha * x otherwise, where the slope alpha
+/// is learned for each channel (`LearnableParams::Alpha`).
+///
+/// Inputs are [batch, features] or multi-dimensional tensors whose channels
+/// axis is given by the `DataFormat` (axis 1 or the last axis).
+pub struct PReLU {
+    initial_slope: f64,
+    data_format: DataFormat,
+    input: Tensor,
+    // one slope per channel: [1, channels]
+    alpha: Tensor,
+    alpha_grad: Tensor,
+    trainable: bool,
+    output_shape: Vec<usize>,
+}
+
+impl PReLU {
+    /// Every slope starts at `initial_slope` (usually 0.25)
+    pub fn new(initial_slope: f64, data_format: DataFormat) -> PReLU {
+        PReLU {
+            initial_slope,
+            data_format,
+            input: Tensor::new(vec![], vec![]),
+            alpha: Tensor::new(vec![], vec![]),
+            alpha_grad: Tensor::new(vec![], vec![]),
+            trainable: true,
+            output_shape: vec![],
+        }
+    }
+
+    // Create the slopes the first time the number of channels is known
+    fn init_params(&mut self, channels: usize) {
+        if self.alpha.data.len() != channels {
+            self.alpha = Tensor::new(vec![self.initial_slope; channels], vec![1, channels]);
+        }
+    }
+
+    // Return the number of channels and a function giving the channel of
+    // each flat index for a tensor of shape `shape` (with the batch dimension)
+    fn channels(&self, shape: &[usize]) -> (usize, impl Fn(usize) -> usize) {
+        let (channels, inner) = match self.data_format {
+            DataFormat::ChannelsFirst => (shape[1], shape[2..].iter().product::<usize>()),
+            DataFormat::ChannelsLast => (shape[shape.len() - 1], 1),
+        };
+        (channels, move |index: usize| (index / inner) % channels)
+    }
+}
+
+impl Layer for PReLU {
+    fn get_info(&self) -> LayerInfo {
+        LayerInfo {
+            layer_type: "PReLU".to_string(),
+            output_shape: self.output_shape.to_vec(),
+            trainable_param: self.alpha.data.len(),
+            non_trainable_param: 0,
+        }
+    }
+
+    fn build(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, String> {
+        if input_shape.is_empty() {
+            return Err("expected at least a 1D input shape".to_string());
+        }
+        let channels = match self.data_format {
+            DataFormat::ChannelsFirst => input_shape[0],
+            DataFormat::ChannelsLast => input_shape[input_shape.len() - 1],
+        };
+        self.init_params(channels);
+        self.output_shape = input_shape.to_vec();
+        Ok(self.output_shape.to_vec())
+    }
+
+    fn forward(&mut self, input: Tensor, _training: bool) -> Tensor {
+        assert!(input.shape.len() >= 2, "PReLU expects at least 2D inputs, got shape {:?}.", input.shape);
+        let (channels, channel_of) = self.channels(&input.shape);
+        self.init_params(channels);
+
+        let data = input.data.iter().enumerate()
+            .map(|(i, x)| if *x > 0.0 { *x } else { self.alpha.data[channel_of(i)] * x })
+            .collect();
+        let output = Tensor::new(data, input.shape.to_vec());
+        self.input = input;
+        output
+    }
+
+    fn backward(&mut self, gradient: &Tensor) -> Tensor {
+        let (channels, channel_of) = self.channels(&self.input.shape);
+
+        let mut alpha_grad = vec![0.0; channels];
+        let mut input_grad = Vec::with_capacity(gradient.data.len());
+        for (i, (grad, x)) in gradient.data.iter().zip(&self.input.data).enumerate() {
+            if *x > 0.0 {
+                input_grad.push(*grad);
+            } else {
+                let c = channel_of(i);
+                alpha_grad[c] += grad * x;
+                input_grad.push(grad * self.alpha.data[c]);
+            }
+        }
+        self.alpha_grad = Tensor::new(alpha_grad, self.alpha.shape.to_vec());
+
+        Tensor::new(input_grad, gradient.shape.to_vec())
+    }
+
+    fn get_params_list(&self) -> Vec<LearnableParams> {
+        vec![LearnableParams::Alpha]
+    }
+
+    fn get_grad(&self, param: &LearnableParams) -> &Tensor {
+        match param {
+            LearnableParams::Alpha => &self.alpha_grad,
+            _ => panic!("PReLU does not have {:?} parameters.", param),
+        }
+    }
+
+    fn get_param(&mut self, param: &LearnableParams) -> &mut Tensor {
+        match param {
+            LearnableParams::Alpha => &mut self.alpha,
+            _ => panic!("PReLU does not have {:?} parameters.", param),
+        }
+    }
+
+    fn is_trainable(&self) -> bool {
+        self.trainable
+    }
+
+    fn set_trainable(&mut self, trainable: bool) {
+        self.trainable = trainable;
+    }
+}
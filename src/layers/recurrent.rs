@@ -0,0 +1,414 @@
+use crate::layers::layer::LayerInfo;
+use crate::layers::layer::Layer;
+use crate::layers::layer::LearnableParams;
+use crate::initializers::Initializer;
+use crate::random::SeedSequence;
+use crate::tensor::Tensor;
+
+/// Cell computing the new state of a `Recurrent` layer at each time step
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CellType {
+    /// h = tanh(x W + h U + b)
+    SimpleRNN,
+    /// Long Short-Term Memory with input, forget, cell and output gates
+    /// (in this order in the weight matrices) and a cell state
+    LSTM,
+    /// Gated Recurrent Unit with update, reset and candidate gates
+    /// (in this order in the weight matrices)
+    GRU,
+}
+
+impl CellType {
+    fn gates(&self) -> usize {
+        match self {
+            CellType::SimpleRNN => 1,
+            CellType::LSTM => 4,
+            CellType::GRU => 3,
+        }
+    }
+
+    // number of state tensors (hidden state and cell state for LSTM)
+    pub(crate) fn states(&self) -> usize {
+        match self {
+            CellType::LSTM => 2,
+            _ => 1,
+        }
+    }
+}
+
+// Values saved at each time step for backpropagation through time
+struct Step {
+    x: Tensor,
+    h_prev: Tensor,
+    c_prev: Tensor,
+    // activated gates (SimpleRNN: [h], LSTM: [i, f, g, o], GRU: [z, r, candidate])
+    gates: Vec<Tensor>,
+    // LSTM: new cell state, GRU: r * h_prev
+    extra: Tensor,
+}
+
+/// Recurrent layer (SimpleRNN, LSTM or GRU) over inputs of shape
+/// [batch, time, features]. The output is the last hidden state
+/// [batch, units] or every hidden state [batch, time, units] when
+/// `return_sequences` is set. Gradients are computed by backpropagation
+/// through time.
+///
+/// Weights are stored as [features, gates * units] (input kernel),
+/// [units, gates * units] (recurrent kernel) and [1, gates * units] (biases).
+pub struct Recurrent {
+    seed: u32,
+    cell: CellType,
+    units: usize,
+    weights: Tensor,
+    recurrent_weights: Tensor,
+    biases: Tensor,
+    weights_grad: Tensor,
+    recurrent_weights_grad: Tensor,
+    biases_grad: Tensor,
+    return_sequences: bool,
+    go_backwards: bool,
+    initial_state: Vec<Tensor>,
+    input_shape: Vec<usize>,
+    steps: Vec<Step>,
+    trainable: bool,
+    output_shape: Vec<usize>,
+}
+
+impl Recurrent {
+    /// Create a layer returning the last state, processing the time steps in
+    /// order from a zero initial state. Use the `with_*` methods to change it.
+    pub fn new(cell: CellType, input_features: usize, units: usize, seed: u32) -> Recurrent {
+        let gates = cell.gates();
+        let seeds = SeedSequence::new(seed);
+
+        let mut biases = Tensor::zero(vec![1, gates * units]);
+        if cell == CellType::LSTM {
+            // a forget gate initially open helps to learn long dependencies
+            for value in &mut biases.data[units..2 * units] {
+                *value = 1.0;
+            }
+        }
+
+        Recurrent {
+            seed,
+            cell,
+            units,
+            weights: Initializer::GlorotUniform.initialize(vec![input_features, gates * units], input_features, units, seeds.child(0).seed()),
+            recurrent_weights: Initializer::LeCunNormal.initialize(vec![units, gates * units], units, units, seeds.child(1).seed()),
+            biases,
+            weights_grad: Tensor::new(vec![], vec![]),
+            recurrent_weights_grad: Tensor::new(vec![], vec![]),
+            biases_grad: Tensor::new(vec![], vec![]),
+            return_sequences: false,
+            go_backwards: false,
+            initial_state: vec![],
+            input_shape: vec![],
+            steps: vec![],
+            trainable: true,
+            output_shape: vec![],
+        }
+    }
+
+    /// Initialize the input kernel with `initializer` (Glorot uniform by default, as `Dense`)
+    pub fn with_kernel_initializer(mut self, initializer: Initializer) -> Recurrent {
+        let shape = self.weights.shape.to_vec();
+        self.weights = initializer.initialize(shape.to_vec(), shape[0], self.units, SeedSequence::new(self.seed).child(0).seed());
+        self
+    }
+
+    /// Initialize the recurrent kernel with `initializer` (LeCun normal by
+    /// default), e.g. `Initializer::Orthogonal` keeps the norm of the states
+    pub fn with_recurrent_initializer(mut self, initializer: Initializer) -> Recurrent {
+        let shape = self.recurrent_weights.shape.to_vec();
+        self.recurrent_weights = initializer.initialize(shape, self.units, self.units, SeedSequence::new(self.seed).child(1).seed());
+        self
+    }
+
+    /// Return the hidden state of every time step instead of the last one
+    pub fn with_return_sequences(mut self, return_sequences: bool) -> Recurrent {
+        self.return_sequences = return_sequences;
+        self
+    }
+
+    /// Process the time steps from the last to the first one. The returned
+    /// sequence is in processing order (reversed).
+    pub fn with_go_backwards(mut self, go_backwards: bool) -> Recurrent {
+        self.go_backwards = go_backwards;
+        self
+    }
+
+    /// Set the initial hidden state (and cell state for LSTM). Each tensor has
+    /// the shape [1, units] (shared by every sample) or [batch, units].
+    pub fn with_initial_state(mut self, initial_state: Vec<Tensor>) -> Recurrent {
+        assert_eq!(initial_state.len(), self.cell.states(), "{:?} expects {} initial state tensors.", self.cell, self.cell.states());
+        for state in &initial_state {
+            assert!(state.shape.len() == 2 && state.shape[1] == self.units, "Initial states must have the shape [batch, {}].", self.units);
+        }
+        self.initial_state = initial_state;
+        self
+    }
+
+    pub(crate) fn units(&self) -> usize {
+        self.units
+    }
+
+    pub(crate) fn returns_sequences(&self) -> bool {
+        self.return_sequences
+    }
+
+    // Initial state `index` for a batch of `batch` samples
+    fn get_initial_state(&self, index: usize, batch: usize) -> Tensor {
+        match self.initial_state.get(index) {
+            None => Tensor::zero(vec![batch, self.units]),
+            Some(state) if state.shape[0] == 1 => {
+                Tensor::new(state.data.repeat(batch), vec![batch, self.units])
+            }
+            Some(state) => {
+                assert_eq!(state.shape[0], batch, "Initial state has a batch size of {} instead of {}.", state.shape[0], batch);
+                state.clone()
+            }
+        }
+    }
+
+    // Index in the sequence of the `step`-th processed time step
+    fn time_index(&self, step: usize, time: usize) -> usize {
+        if self.go_backwards { time - 1 - step } else { step }
+    }
+}
+
+fn sigmoid(x: f64) -> f64 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+// Columns `start..end` of a 2D tensor
+fn columns(tensor: &Tensor, start: usize, end: usize) -> Tensor {
+    let cols = tensor.shape[1];
+    let mut data = Vec::with_capacity(tensor.shape[0] * (end - start));
+    for row in tensor.data.chunks(cols) {
+        data.extend_from_slice(&row[start..end]);
+    }
+    Tensor::new(data, vec![tensor.shape[0], end - start])
+}
+
+// Concatenate 2D tensors having the same number of rows along the columns
+fn concat_columns(tensors: &[Tensor]) -> Tensor {
+    let rows = tensors[0].shape[0];
+    let cols: usize = tensors.iter().map(|t| t.shape[1]).sum();
+    let mut data = Vec::with_capacity(rows * cols);
+    for i in 0..rows {
+        for tensor in tensors {
+            let width = tensor.shape[1];
+            data.extend_from_slice(&tensor.data[i * width..(i + 1) * width]);
+        }
+    }
+    Tensor::new(data, vec![rows, cols])
+}
+
+// Slice [batch, features] at time `t` of a [batch, time, features] tensor
+pub(crate) fn time_slice(tensor: &Tensor, t: usize) -> Tensor {
+    let (batch, time, features) = (tensor.shape[0], tensor.shape[1], tensor.shape[2]);
+    let mut data = Vec::with_capacity(batch * features);
+    for n in 0..batch {
+        let start = (n * time + t) * features;
+        data.extend_from_slice(&tensor.data[start..start + features]);
+    }
+    Tensor::new(data, vec![batch, features])
+}
+
+// Write `values` [batch, features] at time `t` of a [batch, time, features] buffer
+pub(crate) fn set_time_slice(data: &mut [f64], time: usize, t: usize, values: &Tensor) {
+    let (batch, features) = (values.shape[0], values.shape[1]);
+    for n in 0..batch {
+        let start = (n * time + t) * features;
+        data[start..start + features].copy_from_slice(&values.data[n * features..(n + 1) * features]);
+    }
+}
+
+impl Layer for Recurrent {
+    fn get_info(&self) -> LayerInfo {
+        LayerInfo {
+            layer_type: format!("{:?} {}", self.cell, self.units),
+            output_shape: self.output_shape.to_vec(),
+            trainable_param: self.weights.data.len() + self.recurrent_weights.data.len() + self.biases.data.len(),
+            non_trainable_param: 0,
+        }
+    }
+
+    fn build(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, String> {
+        let features = self.weights.shape[0];
+        let time = match input_shape {
+            [time, f] if *f == features => *time,
+            _ => return Err(format!("expected an input shape [time, {}], got {:?}", features, input_shape)),
+        };
+        self.output_shape = if self.return_sequences { vec![time, self.units] } else { vec![self.units] };
+        Ok(self.output_shape.to_vec())
+    }
+
+    fn forward(&mut self, input: Tensor, _training: bool) -> Tensor {
+        assert!(input.shape.len() == 3 && input.shape[2] == self.weights.shape[0],
+            "{:?} expects inputs of shape [batch, time, {}], got {:?}.", self.cell, self.weights.shape[0], input.shape);
+        let (batch, time) = (input.shape[0], input.shape[1]);
+        let units = self.units;
+
+        let mut h = self.get_initial_state(0, batch);
+        let mut c = self.get_initial_state(1, batch);
+        let mut sequence = vec![0.0; batch * time * units];
+        self.steps.clear();
+
+        for step in 0..time {
+            let x = time_slice(&input, self.time_index(step, time));
+            let (h_new, c_new, gates, extra) = match self.cell {
+                CellType::SimpleRNN => {
+                    let h_new = (&x * &self.weights + &(&h * &self.recurrent_weights) + &self.biases).map(|v| v.tanh());
+                    (h_new.clone(), c.clone(), vec![h_new], Tensor::new(vec![], vec![]))
+                }
+                CellType::LSTM => {
+                    let z = &x * &self.weights + &(&h * &self.recurrent_weights) + &self.biases;
+                    let i = columns(&z, 0, units).map(sigmoid);
+                    let f = columns(&z, units, 2 * units).map(sigmoid);
+                    let g = columns(&z, 2 * units, 3 * units).map(|v| v.tanh());
+                    let o = columns(&z, 3 * units, 4 * units).map(sigmoid);
+                    let c_new = f.mult_el(&c) + i.mult_el(&g);
+                    let h_new = o.mult_el(&c_new.map(|v| v.tanh()));
+                    (h_new, c_new.clone(), vec![i, f, g, o], c_new)
+                }
+                CellType::GRU => {
+                    let xw = &x * &self.weights + &self.biases;
+                    let hu = &h * &columns(&self.recurrent_weights, 0, 2 * units);
+                    let z = (columns(&xw, 0, units) + &columns(&hu, 0, units)).map(sigmoid);
+                    let r = (columns(&xw, units, 2 * units) + &columns(&hu, units, 2 * units)).map(sigmoid);
+                    let rh = r.mult_el(&h);
+                    let candidate = (columns(&xw, 2 * units, 3 * units)
+                        + &(&rh * &columns(&self.recurrent_weights, 2 * units, 3 * units))).map(|v| v.tanh());
+                    // h = z * h_prev + (1 - z) * candidate
+                    let h_new = z.mult_el(&h) + z.map(|v| 1.0 - v).mult_el(&candidate);
+                    (h_new, c.clone(), vec![z, r, candidate], rh)
+                }
+            };
+
+            set_time_slice(&mut sequence, time, step, &h_new);
+            self.steps.push(Step { x, h_prev: h, c_prev: c, gates, extra });
+            h = h_new;
+            c = c_new;
+        }
+        self.input_shape = input.shape;
+
+        if self.return_sequences {
+            Tensor::new(sequence, vec![batch, time, units])
+        } else {
+            h
+        }
+    }
+
+    fn backward(&mut self, gradient: &Tensor) -> Tensor {
+        let (batch, time, features) = (self.input_shape[0], self.input_shape[1], self.input_shape[2]);
+        let units = self.units;
+
+        let mut weights_grad = Tensor::zero(self.weights.shape.to_vec());
+        let mut recurrent_weights_grad = Tensor::zero(self.recurrent_weights.shape.to_vec());
+        let mut biases_grad = Tensor::zero(self.biases.shape.to_vec());
+        let mut input_grad = vec![0.0; batch * time * features];
+
+        // gradient flowing from the next time step
+        let mut dh_next = Tensor::zero(vec![batch, units]);
+        let mut dc_next = Tensor::zero(vec![batch, units]);
+
+        for step in (0..time).rev() {
+            let s = &self.steps[step];
+            let dh = if self.return_sequences {
+                time_slice(gradient, step) + &dh_next
+            } else if step == time - 1 {
+                gradient + &dh_next
+            } else {
+                dh_next.clone()
+            };
+
+            // gradient of the pre-activations of the gates
+            let (dz, dh_prev) = match self.cell {
+                CellType::SimpleRNN => {
+                    let dz = dh.mult_el(&s.gates[0].map(|v| 1.0 - v * v));
+                    let dh_prev = &dz * &self.recurrent_weights.get_transpose();
+                    (dz, dh_prev)
+                }
+                CellType::LSTM => {
+                    let (i, f, g, o) = (&s.gates[0], &s.gates[1], &s.gates[2], &s.gates[3]);
+                    let tanh_c = s.extra.map(|v| v.tanh());
+                    let dc = dc_next + &dh.mult_el(o).mult_el(&tanh_c.map(|v| 1.0 - v * v));
+                    let dzi = dc.mult_el(g).mult_el(&i.map(|v| v * (1.0 - v)));
+                    let dzf = dc.mult_el(&s.c_prev).mult_el(&f.map(|v| v * (1.0 - v)));
+                    let dzg = dc.mult_el(i).mult_el(&g.map(|v| 1.0 - v * v));
+                    let dzo = dh.mult_el(&tanh_c).mult_el(&o.map(|v| v * (1.0 - v)));
+                    dc_next = dc.mult_el(f);
+
+                    let dz = concat_columns(&[dzi, dzf, dzg, dzo]);
+                    let dh_prev = &dz * &self.recurrent_weights.get_transpose();
+                    (dz, dh_prev)
+                }
+                CellType::GRU => {
+                    let (z, r, candidate) = (&s.gates[0], &s.gates[1], &s.gates[2]);
+                    let u_zr = columns(&self.recurrent_weights, 0, 2 * units);
+                    let u_candidate = columns(&self.recurrent_weights, 2 * units, 3 * units);
+
+                    let dzc = dh.mult_el(&z.map(|v| 1.0 - v)).mult_el(&candidate.map(|v| 1.0 - v * v));
+                    let drh = &dzc * &u_candidate.get_transpose();
+                    let dzz = dh.mult_el(&(&s.h_prev - candidate)).mult_el(&z.map(|v| v * (1.0 - v)));
+                    let dzr = drh.mult_el(&s.h_prev).mult_el(&r.map(|v| v * (1.0 - v)));
+                    let dz_zr = concat_columns(&[dzz, dzr]);
+
+                    // the candidate uses r * h_prev instead of h_prev
+                    let du = concat_columns(&[&s.h_prev.get_transpose() * &dz_zr, &s.extra.get_transpose() * &dzc]);
+                    recurrent_weights_grad = recurrent_weights_grad + &du;
+
+                    let dh_prev = dh.mult_el(z) + &drh.mult_el(r) + &(&dz_zr * &u_zr.get_transpose());
+                    (concat_columns(&[dz_zr, dzc]), dh_prev)
+                }
+            };
+
+            weights_grad = weights_grad + &(&s.x.get_transpose() * &dz);
+            if self.cell != CellType::GRU {
+                recurrent_weights_grad = recurrent_weights_grad + &(&s.h_prev.get_transpose() * &dz);
+            }
+            biases_grad = biases_grad + &dz.get_sum(0);
+
+            let dx = &dz * &self.weights.get_transpose();
+            set_time_slice(&mut input_grad, time, self.time_index(step, time), &dx);
+            dh_next = dh_prev;
+        }
+
+        self.weights_grad = weights_grad;
+        self.recurrent_weights_grad = recurrent_weights_grad;
+        self.biases_grad = biases_grad;
+
+        Tensor::new(input_grad, self.input_shape.to_vec())
+    }
+
+    fn get_params_list(&self) -> Vec<LearnableParams> {
+        vec![LearnableParams::Weights, LearnableParams::RecurrentWeights, LearnableParams::Biases]
+    }
+
+    fn get_grad(&self, param: &LearnableParams) -> &Tensor {
+        match param {
+            LearnableParams::Weights => &self.weights_grad,
+            LearnableParams::RecurrentWeights => &self.recurrent_weights_grad,
+            LearnableParams::Biases => &self.biases_grad,
+            _ => panic!("{:?} does not have {:?} parameters.", self.cell, param),
+        }
+    }
+
+    fn get_param(&mut self, param: &LearnableParams) -> &mut Tensor {
+        match param {
+            LearnableParams::Weights => &mut self.weights,
+            LearnableParams::RecurrentWeights => &mut self.recurrent_weights,
+            LearnableParams::Biases => &mut self.biases,
+            _ => panic!("{:?} does not have {:?} parameters.", self.cell, param),
+        }
+    }
+
+    fn is_trainable(&self) -> bool {
+        self.trainable
+    }
+
+    fn set_trainable(&mut self, trainable: bool) {
+        self.trainable = trainable;
+    }
+}
@@ -0,0 +1,68 @@
+use crate::layers::layer::LayerInfo;
+use crate::layers::layer::Layer;
+use crate::tensor::Tensor;
+use crate::layers::layer::LearnableParams;
+
+/// Reshape the inputs to [batch, target_shape...]. The number of
+/// elements of each sample must stay the same.
+pub struct Reshape {
+    target_shape: Vec<usize>,
+    input_shape: Vec<usize>,
+}
+
+impl Reshape {
+    pub fn new(target_shape: Vec<usize>) -> Reshape {
+        Reshape {
+            target_shape,
+            input_shape: vec![],
+        }
+    }
+}
+
+impl Layer for Reshape {
+    fn get_info(&self) -> LayerInfo {
+        LayerInfo {
+            layer_type: "Reshape".to_string(),
+            output_shape: self.target_shape.to_vec(),
+            trainable_param: 0,
+            non_trainable_param: 0,
+        }
+    }
+
+    fn build(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, String> {
+        let input_size: usize = input_shape.iter().product();
+        let target_size: usize = self.target_shape.iter().product();
+        if input_size != target_size {
+            return Err(format!("cannot reshape {:?} ({} elements) into {:?} ({} elements)",
+                input_shape, input_size, self.target_shape, target_size));
+        }
+        Ok(self.target_shape.to_vec())
+    }
+
+    fn forward(&mut self, input: Tensor, _training: bool) -> Tensor {
+        let batch = input.shape[0];
+        assert_eq!(input.data.len(), batch * self.target_shape.iter().product::<usize>(),
+            "Cannot reshape {:?} into {:?}.", input.shape, self.target_shape);
+
+        let mut shape = vec![batch];
+        shape.extend(&self.target_shape);
+        self.input_shape = input.shape.to_vec();
+        Tensor::new(input.data, shape)
+    }
+
+    fn backward(&mut self, gradient: &Tensor) -> Tensor {
+        Tensor::new(gradient.data.to_vec(), self.input_shape.to_vec())
+    }
+
+    fn get_params_list(&self) -> Vec<LearnableParams> {
+        vec![]
+    }
+
+    fn get_grad(&self, _param: &LearnableParams) -> &Tensor {
+        panic!("Layer does not have learnable parameters.")
+    }
+
+    fn get_param(&mut self, _param: &LearnableParams) -> &mut Tensor {
+        panic!("Layer does not have learnable parameters.")
+    }
+}
@@ -4,20 +4,26 @@ use crate::tensor::Tensor;
 use crate::layers::layer::LearnableParams;
 
 pub struct Sigmoid {
-    input: Tensor
+    input: Tensor,
+    output_shape: Vec<usize>
 }
 
 impl Layer for Sigmoid {
     fn get_info(&self) -> LayerInfo {
         LayerInfo {
             layer_type: format!("Sigmoid"),
-            output_shape: self.input.shape.to_vec(),
+            output_shape: self.output_shape.to_vec(),
             trainable_param: 0,
             non_trainable_param: 0,
 
         }
     }
 
+    fn build(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, String> {
+        self.output_shape = input_shape.to_vec();
+        Ok(self.output_shape.to_vec())
+    }
+
     fn forward(&mut self, input: Tensor, _training: bool) -> Tensor {
         self.input = input;
         self.input.map(|x| Sigmoid::sigmoid(x))
@@ -53,7 +59,8 @@ impl Sigmoid {
 
     pub fn new() -> Sigmoid {
         Sigmoid {
-            input: Tensor::new(vec![], vec![])
+            input: Tensor::new(vec![], vec![]),
+            output_shape: vec![]
         }
     }
 
@@ -4,19 +4,29 @@ use crate::tensor::Tensor;
 use crate::layers::layer::LearnableParams;
 
 pub struct Softmax {
-    input: Tensor
+    input: Tensor,
+    output_shape: Vec<usize>
 }
 
 impl Layer for Softmax {
     fn get_info(&self) -> LayerInfo {
         LayerInfo {
             layer_type: format!("Softmax"),
-            output_shape: self.input.shape.to_vec(),
+            output_shape: self.output_shape.to_vec(),
             trainable_param: 0,
             non_trainable_param: 0,
         }
     }
 
+    fn build(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, String> {
+        // the softmax is computed over the rows of 2D inputs
+        if input_shape.len() != 1 {
+            return Err(format!("expected a 1D input shape, got {:?} (add a Flatten layer)", input_shape));
+        }
+        self.output_shape = input_shape.to_vec();
+        Ok(self.output_shape.to_vec())
+    }
+
     fn forward(&mut self, input: Tensor, _training: bool) -> Tensor {
         self.input = input;
 
@@ -43,7 +53,8 @@ impl Layer for Softmax {
 impl Softmax {
     pub(crate) fn new() -> Softmax {
         Softmax {
-            input: Tensor::new(vec![], vec![])
+            input: Tensor::new(vec![], vec![]),
+            output_shape: vec![]
         }
     }
 
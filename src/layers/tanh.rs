@@ -4,19 +4,25 @@ use crate::tensor::Tensor;
 use crate::layers::layer::LearnableParams;
 
 pub struct TanH {
-    input: Tensor
+    input: Tensor,
+    output_shape: Vec<usize>
 }
 
 impl Layer for TanH {
     fn get_info(&self) -> LayerInfo {
         LayerInfo {
             layer_type: format!("tanH"),
-            output_shape: vec![],
+            output_shape: self.output_shape.to_vec(),
             trainable_param: 0,
             non_trainable_param: 0,
         }
     }
 
+    fn build(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, String> {
+        self.output_shape = input_shape.to_vec();
+        Ok(self.output_shape.to_vec())
+    }
+
     fn forward(&mut self, input: Tensor, _training: bool) -> Tensor {
         self.input = input;
         self.input.map(|x| TanH::tanh(x))
@@ -52,7 +58,8 @@ impl TanH {
 
     pub fn new() -> TanH {
         TanH {
-            input: Tensor::new(vec![], vec![])
+            input: Tensor::new(vec![], vec![]),
+            output_shape: vec![]
         }
     }
 }
@@ -0,0 +1,177 @@
+use crate::layers::layer::LayerInfo;
+use crate::layers::layer::Layer;
+use crate::layers::layer::LearnableParams;
+use crate::layers::attention::MultiHeadAttention;
+use crate::layers::dense::Dense;
+use crate::layers::layer_norm::{LayerNorm, NormMode};
+use crate::layers::relu::ReLU;
+use crate::random::SeedSequence;
+use crate::tensor::Tensor;
+
+/// Transformer encoder block over inputs of shape [batch, time, model_dim]:
+///
+/// x = LayerNorm(x + MultiHeadAttention(x))
+/// y = LayerNorm(x + Dense(ReLU(Dense(x))))
+///
+/// Parameters are `LearnableParams::Sublayer(index, _)` with the sublayers
+/// 0: attention, 1: first normalization, 2 and 3: feed-forward network,
+/// 4: second normalization.
+pub struct TransformerEncoderBlock {
+    attention: MultiHeadAttention,
+    attention_norm: LayerNorm,
+    feed_forward: (Dense, ReLU, Dense),
+    feed_forward_norm: LayerNorm,
+    input_shape: Vec<usize>,
+    trainable: bool,
+    output_shape: Vec<usize>,
+}
+
+impl TransformerEncoderBlock {
+    /// The feed-forward network has `feed_forward_dim` hidden units
+    pub fn new(model_dim: usize, heads: usize, feed_forward_dim: usize, seed: u32) -> TransformerEncoderBlock {
+        let seeds = SeedSequence::new(seed);
+        TransformerEncoderBlock {
+            attention: MultiHeadAttention::new(model_dim, heads, seeds.child(0).seed()),
+            attention_norm: LayerNorm::new(NormMode::Layer, 1e-6),
+            feed_forward: (
+                Dense::new(model_dim, feed_forward_dim, seeds.child(1).seed()),
+                ReLU::new(),
+                Dense::new(feed_forward_dim, model_dim, seeds.child(2).seed()),
+            ),
+            feed_forward_norm: LayerNorm::new(NormMode::Layer, 1e-6),
+            input_shape: vec![],
+            trainable: true,
+            output_shape: vec![],
+        }
+    }
+
+    /// See `MultiHeadAttention::with_causal_mask`
+    pub fn with_causal_mask(mut self, causal: bool) -> TransformerEncoderBlock {
+        self.attention = self.attention.with_causal_mask(causal);
+        self
+    }
+
+    /// See `MultiHeadAttention::with_padding_mask`
+    pub fn with_padding_mask(mut self, mask_padding: bool) -> TransformerEncoderBlock {
+        self.attention = self.attention.with_padding_mask(mask_padding);
+        self
+    }
+
+    /// See `MultiHeadAttention::set_padding_mask`
+    pub fn set_padding_mask(&mut self, mask: Option<Tensor>) {
+        self.attention.set_padding_mask(mask);
+    }
+
+    fn sublayer(&self, index: usize) -> &dyn Layer {
+        match index {
+            0 => &self.attention,
+            1 => &self.attention_norm,
+            2 => &self.feed_forward.0,
+            3 => &self.feed_forward.2,
+            4 => &self.feed_forward_norm,
+            _ => panic!("TransformerEncoderBlock only has 5 sublayers."),
+        }
+    }
+
+    fn sublayer_mut(&mut self, index: usize) -> &mut dyn Layer {
+        match index {
+            0 => &mut self.attention,
+            1 => &mut self.attention_norm,
+            2 => &mut self.feed_forward.0,
+            3 => &mut self.feed_forward.2,
+            4 => &mut self.feed_forward_norm,
+            _ => panic!("TransformerEncoderBlock only has 5 sublayers."),
+        }
+    }
+}
+
+impl Layer for TransformerEncoderBlock {
+    fn get_info(&self) -> LayerInfo {
+        let attention = self.attention.get_info();
+        let (dense_in, _, dense_out) = &self.feed_forward;
+        let (model_dim, hidden) = (dense_out.get_info().output_shape[0], dense_in.get_info().output_shape[0]);
+        LayerInfo {
+            layer_type: format!("TransformerEncoderBlock {}", self.attention.heads()),
+            output_shape: self.output_shape.to_vec(),
+            // attention, feed-forward network, gamma and beta of both normalizations
+            trainable_param: attention.trainable_param
+                + model_dim * hidden + hidden
+                + hidden * model_dim + model_dim
+                + 4 * model_dim,
+            non_trainable_param: 0,
+        }
+    }
+
+    fn build(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, String> {
+        self.output_shape = self.attention.build(input_shape)?;
+        // the feed-forward network is applied to each time step
+        let hidden_shape = self.feed_forward.0.build(&input_shape[1..])?;
+        self.feed_forward.2.build(&hidden_shape)?;
+        self.attention_norm.build(input_shape)?;
+        self.feed_forward_norm.build(input_shape)?;
+        Ok(self.output_shape.to_vec())
+    }
+
+    fn forward(&mut self, input: Tensor, training: bool) -> Tensor {
+        self.input_shape = input.shape.to_vec();
+        let (batch, time, dim) = (input.shape[0], input.shape[1], input.shape[2]);
+
+        let attention = self.attention.forward(input.clone(), training);
+        let x = self.attention_norm.forward(input + attention, training);
+
+        let flat = Tensor::new(x.data.to_vec(), vec![batch * time, dim]);
+        let (dense_in, relu, dense_out) = &mut self.feed_forward;
+        let hidden = relu.forward(dense_in.forward(flat, training), training);
+        let feed_forward = dense_out.forward(hidden, training);
+
+        self.feed_forward_norm.forward(x + Tensor::new(feed_forward.data, vec![batch, time, dim]), training)
+    }
+
+    fn backward(&mut self, gradient: &Tensor) -> Tensor {
+        let (batch, time, dim) = (self.input_shape[0], self.input_shape[1], self.input_shape[2]);
+
+        // residual of the feed-forward network
+        let sum_grad = self.feed_forward_norm.backward(gradient);
+        let (dense_in, relu, dense_out) = &mut self.feed_forward;
+        let flat_grad = Tensor::new(sum_grad.data.to_vec(), vec![batch * time, dim]);
+        let feed_forward_grad = dense_in.backward(&relu.backward(&dense_out.backward(&flat_grad)));
+        let x_grad = sum_grad + Tensor::new(feed_forward_grad.data, vec![batch, time, dim]);
+
+        // residual of the attention
+        let sum_grad = self.attention_norm.backward(&x_grad);
+        let attention_grad = self.attention.backward(&sum_grad);
+        sum_grad + attention_grad
+    }
+
+    fn get_params_list(&self) -> Vec<LearnableParams> {
+        let mut params = Vec::new();
+        for index in 0..5 {
+            for param in self.sublayer(index).get_params_list() {
+                params.push(LearnableParams::Sublayer(index, Box::new(param)));
+            }
+        }
+        params
+    }
+
+    fn get_grad(&self, param: &LearnableParams) -> &Tensor {
+        match param {
+            LearnableParams::Sublayer(index, param) => self.sublayer(*index).get_grad(param),
+            _ => panic!("TransformerEncoderBlock parameters are LearnableParams::Sublayer, got {:?}.", param),
+        }
+    }
+
+    fn get_param(&mut self, param: &LearnableParams) -> &mut Tensor {
+        match param {
+            LearnableParams::Sublayer(index, param) => self.sublayer_mut(*index).get_param(param),
+            _ => panic!("TransformerEncoderBlock parameters are LearnableParams::Sublayer, got {:?}.", param),
+        }
+    }
+
+    fn is_trainable(&self) -> bool {
+        self.trainable
+    }
+
+    fn set_trainable(&mut self, trainable: bool) {
+        self.trainable = trainable;
+    }
+}
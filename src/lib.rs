@@ -2,8 +2,13 @@ pub mod dataset;
 pub mod layers;
 pub mod loss;
 pub mod optimizers;
+pub mod initializers;
+pub mod regularizers;
+pub mod constraints;
 pub mod metrics;
 pub mod sequential;
+pub mod graph;
 pub mod tensor;
 mod random;
+mod distributions;
 pub mod utils;
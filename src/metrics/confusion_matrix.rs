@@ -1,3 +1,6 @@
+use std::fmt;
+
+use crate::metrics::{Average, MetricError};
 use crate::tensor::Tensor;
 use crate::utils;
 
@@ -12,23 +15,33 @@ pub struct ConfusionMatrix {
 /// >>> metrics::ConfusionMatrix::new(y_true, y_pred)
 /// >>> metrics::ConfusionMatrix.accuracy_score()
 /// >>> metrics::ConfusionMatrix.recall_score(1)
-/// >>> metrics::ConfusionMatrix.precision_score(1)
-/// >>> metrics::ConfusionMatrix.f1_score_score(1)
+/// >>> metrics::ConfusionMatrix.precision(&Average::Macro)
+/// >>> metrics::ConfusionMatrix.f1(&Average::Weighted)
+/// >>> metrics::ConfusionMatrix.classification_report()
 impl ConfusionMatrix {
     pub fn new(y_true: Tensor, y_pred: Tensor) -> ConfusionMatrix {
-        assert_eq!(y_true.shape, y_pred.shape);
+        let mut cm = ConfusionMatrix::empty(y_true.shape[1]);
+        cm.update(&y_true, &y_pred);
+        cm
+    }
 
-        let cm_shape = vec![y_true.shape[1], y_true.shape[1]];
-        let mut cm = vec![vec![0; cm_shape[0]]; cm_shape[0]];
+    /// Create a confusion matrix for `classes` classes without any observation.
+    pub fn empty(classes: usize) -> ConfusionMatrix {
+        ConfusionMatrix { data: vec![vec![0; classes]; classes] }
+    }
 
-        let y_pred_categories = utils::one_hot_encoded_tensor_to_indices(&y_pred);
-        let y_true_categories = utils::one_hot_encoded_tensor_to_indices(&y_true);
+    /// Add the observations of a new batch to the confusion matrix.
+    /// This allows to compute metrics on a dataset batch by batch.
+    pub fn update(&mut self, y_true: &Tensor, y_pred: &Tensor) {
+        assert_eq!(y_true.shape, y_pred.shape);
+        assert_eq!(y_true.shape[1], self.data.len(), "Number of classes differs from the confusion matrix size.");
 
-        for index in 0..y_pred_categories.len() {
-            cm[y_true_categories[index]][y_pred_categories[index]] += 1;
-        }
+        let y_pred_categories = utils::one_hot_encoded_tensor_to_indices(y_pred);
+        let y_true_categories = utils::one_hot_encoded_tensor_to_indices(y_true);
 
-        ConfusionMatrix { data: cm }
+        for (true_category, pred_category) in y_true_categories.iter().zip(y_pred_categories.iter()) {
+            self.data[*true_category][*pred_category] += 1;
+        }
     }
 
     /// Compute accuracy score based on confusion matrix
@@ -45,32 +58,154 @@ impl ConfusionMatrix {
         correct_classif as f64 / cm_sum
     }
 
+    /// Number of observations whose true class is `class`
+    pub fn support(&self, class: usize) -> usize {
+        self.data[class].iter().sum()
+    }
+
     /// The recall for input class is the number of
     /// correctly predicted  input class out of the number of actual input class
     pub fn recall_score(&self, class: usize) -> f64 {
         let correct_class: usize = self.data[class][class];
-        let all_predicted_class = (0..self.data.len())
-            .map(|v| self.data[v][class])
-            .sum::<usize>() as f64;
+        let actual_class = self.support(class);
 
-        correct_class as f64 / all_predicted_class
+        safe_ratio(correct_class as f64, actual_class as f64)
     }
 
     /// The precision for the input class is the number of
     /// correctly predicted input class out of all predicted input class
     pub fn precision_score(&self, class: usize) -> f64 {
         let correct_class: usize = self.data[class][class];
-        let actual_class = (0..self.data.len())
-            .map(|v| self.data[class][v])
+        let all_predicted_class = (0..self.data.len())
+            .map(|v| self.data[v][class])
             .sum::<usize>() as f64;
 
-        correct_class as f64 / actual_class
+        safe_ratio(correct_class as f64, all_predicted_class)
     }
 
     /// Harmonic mean of the precision and recall
     pub fn f1_score(&self, class: usize) -> f64 {
-        (2.0 * self.recall_score(class) * self.precision_score(class))
-            / (self.recall_score(class) + self.precision_score(class))
+        let recall = self.recall_score(class);
+        let precision = self.precision_score(class);
+        safe_ratio(2.0 * recall * precision, recall + precision)
     }
 
+    /// Recall for a single class or averaged over all classes
+    pub fn recall(&self, average: &Average) -> Result<f64, MetricError> {
+        average.check_class(self.data.len())?;
+        Ok(self.average(average, |class| self.recall_score(class)))
+    }
+
+    /// Precision for a single class or averaged over all classes
+    pub fn precision(&self, average: &Average) -> Result<f64, MetricError> {
+        average.check_class(self.data.len())?;
+        Ok(self.average(average, |class| self.precision_score(class)))
+    }
+
+    /// F1 score for a single class or averaged over all classes
+    pub fn f1(&self, average: &Average) -> Result<f64, MetricError> {
+        average.check_class(self.data.len())?;
+        Ok(self.average(average, |class| self.f1_score(class)))
+    }
+
+    /// Get the precision, recall, F1 score and support of every class
+    pub fn classification_report(&self) -> ClassificationReport {
+        let classes = (0..self.data.len())
+            .map(|class| ClassReport {
+                class,
+                precision: self.precision_score(class),
+                recall: self.recall_score(class),
+                f1: self.f1_score(class),
+                support: self.support(class),
+            })
+            .collect();
+
+        ClassificationReport {
+            classes,
+            accuracy: self.accuracy_score(),
+            macro_avg: self.average_report(&Average::Macro),
+            weighted_avg: self.average_report(&Average::Weighted),
+        }
+    }
+
+    fn average_report(&self, average: &Average) -> AverageReport {
+        AverageReport {
+            precision: self.average(average, |class| self.precision_score(class)),
+            recall: self.average(average, |class| self.recall_score(class)),
+            f1: self.average(average, |class| self.f1_score(class)),
+        }
+    }
+
+    // Apply the per-class `score` function according to `average`
+    // (`Average::Class` is checked by the caller)
+    fn average<F: Fn(usize) -> f64>(&self, average: &Average, score: F) -> f64 {
+        let classes = self.data.len();
+        match average {
+            Average::Class(class) => score(*class),
+            // with micro averaging, false negatives of a class are false positives
+            // of another one so micro recall, precision and F1 are the accuracy
+            Average::Micro => self.accuracy_score(),
+            Average::Macro => {
+                (0..classes).map(&score).sum::<f64>() / classes as f64
+            }
+            Average::Weighted => {
+                let total: usize = (0..classes).map(|class| self.support(class)).sum();
+                let weighted_sum: f64 = (0..classes)
+                    .map(|class| score(class) * self.support(class) as f64)
+                    .sum();
+                safe_ratio(weighted_sum, total as f64)
+            }
+        }
+    }
+}
+
+/// Metrics of a single class
+#[derive(Debug)]
+pub struct ClassReport {
+    pub class: usize,
+    pub precision: f64,
+    pub recall: f64,
+    pub f1: f64,
+    pub support: usize,
+}
+
+/// Metrics averaged over all classes
+#[derive(Debug)]
+pub struct AverageReport {
+    pub precision: f64,
+    pub recall: f64,
+    pub f1: f64,
+}
+
+/// Precision, recall, F1 score and support of every class, and their
+/// macro and weighted averages.
+#[derive(Debug)]
+pub struct ClassificationReport {
+    pub classes: Vec<ClassReport>,
+    pub accuracy: f64,
+    pub macro_avg: AverageReport,
+    pub weighted_avg: AverageReport,
+}
+
+impl fmt::Display for ClassificationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let support: usize = self.classes.iter().map(|c| c.support).sum();
+
+        writeln!(f, "{:>14}{:>11}{:>11}{:>11}{:>11}", "", "precision", "recall", "f1-score", "support")?;
+        for c in &self.classes {
+            writeln!(f, "{:>14}{:>11.4}{:>11.4}{:>11.4}{:>11}", c.class, c.precision, c.recall, c.f1, c.support)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "{:>14}{:>11}{:>11}{:>11.4}{:>11}", "accuracy", "", "", self.accuracy, support)?;
+        for (name, avg) in [("macro avg", &self.macro_avg), ("weighted avg", &self.weighted_avg)].iter() {
+            writeln!(f, "{:>14}{:>11.4}{:>11.4}{:>11.4}{:>11}", name, avg.precision, avg.recall, avg.f1, support)?;
+        }
+        Ok(())
+    }
+}
+
+// Return numerator / denominator, or 0 if the denominator is 0
+// (e.g. precision of a class that is never predicted)
+fn safe_ratio(numerator: f64, denominator: f64) -> f64 {
+    if denominator == 0.0 { 0.0 } else { numerator / denominator }
 }
@@ -1,8 +1,264 @@
+use std::cmp;
+use std::fmt;
+
+use crate::tensor::Tensor;
+use confusion_matrix::ConfusionMatrix;
+use probabilistic::ProbabilityScores;
+use regression::RegressionStats;
+use streaming_metric::StreamingMetric;
+
 pub mod confusion_matrix;
+pub mod regression;
+pub mod probabilistic;
+pub mod streaming_metric;
 
+#[derive(Debug)]
 pub enum Metric {
     Accuracy,
-    Recall,
-    Precision,
-    F1,
+    Recall(Average),
+    Precision(Average),
+    F1(Average),
+    /// Mean absolute error
+    MAE,
+    /// Root mean squared error
+    RMSE,
+    /// Coefficient of determination
+    R2,
+    /// Mean absolute percentage error
+    MAPE,
+    ExplainedVariance,
+    /// Area under the ROC curve (one-vs-rest for multi-class problems)
+    RocAuc(Average),
+    /// Area under the precision-recall curve
+    AveragePrecision(Average),
+    LogLoss,
+    BrierScore,
+    /// User-defined metric
+    Custom(Box<dyn StreamingMetric>),
+}
+
+impl Metric {
+    /// Return true if the metric is computed from the confusion matrix
+    pub fn is_classification(&self) -> bool {
+        matches!(self, Metric::Accuracy | Metric::Recall(_) | Metric::Precision(_) | Metric::F1(_))
+    }
+
+    /// Return true if the metric is computed from the predicted probabilities
+    pub fn is_probabilistic(&self) -> bool {
+        matches!(self, Metric::RocAuc(_) | Metric::AveragePrecision(_) | Metric::LogLoss | Metric::BrierScore)
+    }
+
+    /// Return true if the metric is computed from regression statistics
+    pub fn is_regression(&self) -> bool {
+        matches!(self, Metric::MAE | Metric::RMSE | Metric::R2 | Metric::MAPE | Metric::ExplainedVariance)
+    }
+
+    /// Get the streaming implementation of the metric
+    pub(crate) fn into_streaming(self) -> Box<dyn StreamingMetric> {
+        match self {
+            Metric::Custom(metric) => metric,
+            metric => Box::new(BuiltinMetric {
+                metric,
+                cm: None,
+                stats: None,
+                scores: None,
+            }),
+        }
+    }
+}
+
+impl fmt::Display for Metric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Metric::Accuracy => write!(f, "Accuracy"),
+            Metric::Recall(average) => write!(f, "Recall ({})", average),
+            Metric::Precision(average) => write!(f, "Precision ({})", average),
+            Metric::F1(average) => write!(f, "F1 Score ({})", average),
+            Metric::MAE => write!(f, "MAE"),
+            Metric::RMSE => write!(f, "RMSE"),
+            Metric::R2 => write!(f, "R2"),
+            Metric::MAPE => write!(f, "MAPE"),
+            Metric::ExplainedVariance => write!(f, "Explained Variance"),
+            Metric::RocAuc(average) => write!(f, "ROC AUC ({})", average),
+            Metric::AveragePrecision(average) => write!(f, "Average Precision ({})", average),
+            Metric::LogLoss => write!(f, "Log Loss"),
+            Metric::BrierScore => write!(f, "Brier Score"),
+            Metric::Custom(metric) => write!(f, "{}", metric.name()),
+        }
+    }
+}
+
+/// How per-class metrics (recall, precision, F1) are reduced to a single value
+#[derive(Clone, Debug, PartialEq)]
+pub enum Average {
+    /// Metric of the class at the index specified
+    Class(usize),
+    /// Unweighted mean of the metric of every class
+    Macro,
+    /// Metric computed from the total true positives, false negatives
+    /// and false positives of all classes
+    Micro,
+    /// Mean of the metric of every class weighted by its support
+    Weighted,
+}
+
+impl Average {
+    /// Check that `Average::Class` selects one of the `classes` classes
+    pub fn check_class(&self, classes: usize) -> Result<(), MetricError> {
+        match self {
+            Average::Class(class) if *class >= classes => {
+                Err(MetricError::UnknownClass { class: *class, classes })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for Average {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Average::Class(class) => write!(f, "class {}", class),
+            Average::Macro => write!(f, "macro"),
+            Average::Micro => write!(f, "micro"),
+            Average::Weighted => write!(f, "weighted"),
+        }
+    }
+}
+
+/// Error returned when a metric cannot be computed
+#[derive(Debug, PartialEq)]
+pub enum MetricError {
+    /// `Average::Class` selects a class index out of the `classes` classes
+    UnknownClass { class: usize, classes: usize },
+}
+
+impl fmt::Display for MetricError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MetricError::UnknownClass { class, classes } => {
+                write!(f, "Unknown class {}: the labels only have {} classes.", class, classes)
+            }
+        }
+    }
+}
+
+// Streaming implementation of the built-in metrics. The accumulator
+// matching the metric is created at the first update since the number
+// of outputs is unknown before.
+struct BuiltinMetric {
+    metric: Metric,
+    cm: Option<ConfusionMatrix>,
+    stats: Option<RegressionStats>,
+    scores: Option<ProbabilityScores>,
+}
+
+impl StreamingMetric for BuiltinMetric {
+    fn name(&self) -> String {
+        self.metric.to_string()
+    }
+
+    fn update(&mut self, y_true: &Tensor, y_pred: &Tensor) {
+        let outputs = y_true.shape[1];
+        if self.metric.is_classification() {
+            self.cm.get_or_insert_with(|| ConfusionMatrix::empty(outputs)).update(y_true, y_pred);
+        } else if self.metric.is_regression() {
+            self.stats.get_or_insert_with(|| RegressionStats::new(outputs)).update(y_true, y_pred);
+        } else if self.metric.is_probabilistic() {
+            self.scores.get_or_insert_with(|| ProbabilityScores::new(outputs)).update(y_true, y_pred);
+        }
+    }
+
+    fn check_outputs(&self, outputs: usize) -> Result<(), MetricError> {
+        match &self.metric {
+            Metric::Recall(average) | Metric::Precision(average) | Metric::F1(average) => average.check_class(outputs),
+            // a single output is the probability of the class 1 of a binary problem
+            Metric::RocAuc(average) | Metric::AveragePrecision(average) => average.check_class(cmp::max(outputs, 2)),
+            _ => Ok(()),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.cm = None;
+        self.stats = None;
+        self.scores = None;
+    }
+
+    fn result(&self) -> f64 {
+        if let Some(cm) = &self.cm {
+            let result = match &self.metric {
+                Metric::Accuracy => Ok(cm.accuracy_score()),
+                Metric::Recall(average) => cm.recall(average),
+                Metric::Precision(average) => cm.precision(average),
+                Metric::F1(average) => cm.f1(average),
+                _ => unreachable!(),
+            };
+            // unknown classes are reported by `check_outputs` before the evaluation
+            return result.unwrap_or(f64::NAN);
+        }
+        if let Some(scores) = &self.scores {
+            let result = match &self.metric {
+                Metric::RocAuc(average) => scores.roc_auc(average),
+                Metric::AveragePrecision(average) => scores.average_precision(average),
+                Metric::LogLoss => Ok(scores.log_loss()),
+                Metric::BrierScore => Ok(scores.brier_score()),
+                _ => unreachable!(),
+            };
+            return result.unwrap_or(f64::NAN);
+        }
+        // regression metrics are averaged over all outputs
+        let per_output = self.result_per_output();
+        if per_output.is_empty() { f64::NAN } else { regression::average(&per_output) }
+    }
+
+    fn result_per_output(&self) -> Vec<f64> {
+        match &self.stats {
+            Some(stats) => match &self.metric {
+                Metric::MAE => stats.mae(),
+                Metric::RMSE => stats.rmse(),
+                Metric::R2 => stats.r2_score(),
+                Metric::MAPE => stats.mape(),
+                Metric::ExplainedVariance => stats.explained_variance_score(),
+                _ => unreachable!(),
+            },
+            None => vec![],
+        }
+    }
+}
+
+/// Value of a metric computed during a model evaluation.
+#[derive(Debug)]
+pub struct MetricValue {
+    pub name: String,
+    /// Value of the metric (averaged over all outputs for regression metrics)
+    pub value: f64,
+    /// Value of the metric for each output (target column).
+    /// Empty for classification metrics.
+    pub per_output: Vec<f64>,
+}
+
+/// Result of a model evaluation: the loss and the value of each
+/// metric specified in `compile` (in the same order).
+#[derive(Debug)]
+pub struct Evaluation {
+    pub loss: f64,
+    pub metrics: Vec<MetricValue>,
+}
+
+impl Evaluation {
+    /// Get the value of the metric named `name` (e.g. "Accuracy").
+    /// Return `None` if the metric was not computed.
+    pub fn get(&self, name: &str) -> Option<f64> {
+        self.metrics.iter().find(|m| m.name == name).map(|m| m.value)
+    }
+
+    // Print the value of every metric (and of each output if there are several)
+    pub(crate) fn print_metrics(&self) {
+        for metric in &self.metrics {
+            if metric.per_output.len() > 1 {
+                println!("{}: {:.4} {:.4?}", metric.name, metric.value, metric.per_output);
+            } else {
+                println!("{}: {:.4}", metric.name, metric.value);
+            }
+        }
+    }
 }
@@ -0,0 +1,262 @@
+use std::cmp::{self, Ordering};
+
+use crate::metrics::{Average, MetricError};
+use crate::tensor::Tensor;
+use crate::utils;
+
+// Probabilities are clipped to [EPSILON, 1 - EPSILON] to compute the log loss
+const EPSILON: f64 = 1e-15;
+
+/// Collect predicted probabilities to compute threshold-free classification
+/// metrics (ROC AUC, average precision, log loss and Brier score).
+/// Predictions must be probabilities (e.g. the output of a `Softmax` layer).
+/// A single target column is considered as a binary classification where
+/// the prediction is the probability of the positive class.
+///
+/// Examples
+/// --------
+/// >>> let mut scores = metrics::probabilistic::ProbabilityScores::new(classes)
+/// >>> scores.update(&y_true, &y_pred)
+/// >>> scores.roc_auc(&Average::Macro)
+/// >>> scores.roc_curve(1)
+/// >>> scores.log_loss()
+pub struct ProbabilityScores {
+    classes: usize,
+    // true class of each observation
+    labels: Vec<usize>,
+    // predicted probabilities, `classes` values per observation
+    probabilities: Vec<f64>,
+    sum_log_loss: f64,
+    sum_brier: f64,
+}
+
+/// Receiver operating characteristic curve, from the highest to the
+/// lowest threshold.
+#[derive(Debug)]
+pub struct RocCurve {
+    pub false_positive_rate: Vec<f64>,
+    pub true_positive_rate: Vec<f64>,
+    pub thresholds: Vec<f64>,
+}
+
+/// Precision-recall curve, from the highest to the lowest threshold.
+#[derive(Debug)]
+pub struct PrecisionRecallCurve {
+    pub precision: Vec<f64>,
+    pub recall: Vec<f64>,
+    pub thresholds: Vec<f64>,
+}
+
+impl ProbabilityScores {
+    /// Create an empty collection for `classes` target columns.
+    pub fn new(classes: usize) -> ProbabilityScores {
+        ProbabilityScores {
+            classes,
+            labels: vec![],
+            probabilities: vec![],
+            sum_log_loss: 0.0,
+            sum_brier: 0.0,
+        }
+    }
+
+    /// Add the observations of a new batch.
+    pub fn update(&mut self, y_true: &Tensor, y_pred: &Tensor) {
+        assert_eq!(y_true.shape, y_pred.shape);
+        assert_eq!(y_true.shape[1], self.classes, "Number of classes differs from the collection size.");
+
+        let labels = if self.classes == 1 {
+            y_true.data.iter().map(|y| if *y >= 0.5 { 1 } else { 0 }).collect()
+        } else {
+            utils::one_hot_encoded_tensor_to_indices(y_true)
+        };
+
+        for (row, label) in labels.iter().enumerate() {
+            if self.classes == 1 {
+                let p = y_pred.get_value(row, 0);
+                let y = *label as f64;
+                let p_clipped = p.clamp(EPSILON, 1.0 - EPSILON);
+                self.sum_log_loss -= y * p_clipped.ln() + (1.0 - y) * (1.0 - p_clipped).ln();
+                self.sum_brier += (p - y).powi(2);
+            } else {
+                let p = y_pred.get_value(row, *label);
+                self.sum_log_loss -= p.clamp(EPSILON, 1.0 - EPSILON).ln();
+                self.sum_brier += (0..self.classes)
+                    .map(|class| {
+                        let y = if class == *label { 1.0 } else { 0.0 };
+                        (y_pred.get_value(row, class) - y).powi(2)
+                    })
+                    .sum::<f64>();
+            }
+        }
+
+        self.labels.extend(labels);
+        self.probabilities.extend(y_pred.data.iter());
+    }
+
+    /// Mean negative log-likelihood of the true classes
+    pub fn log_loss(&self) -> f64 {
+        self.sum_log_loss / self.labels.len() as f64
+    }
+
+    /// Mean squared difference between predicted probabilities and one-hot
+    /// encoded true classes (summed over the classes)
+    pub fn brier_score(&self) -> f64 {
+        self.sum_brier / self.labels.len() as f64
+    }
+
+    /// ROC curve of `class` against all the other classes (one-vs-rest)
+    pub fn roc_curve(&self, class: usize) -> RocCurve {
+        let (scores, labels) = self.binary_scores(class);
+        Self::compute_roc_curve(&scores, &labels)
+    }
+
+    /// Precision-recall curve of `class` against all the other classes
+    pub fn precision_recall_curve(&self, class: usize) -> PrecisionRecallCurve {
+        let (scores, labels) = self.binary_scores(class);
+        Self::compute_precision_recall_curve(&scores, &labels)
+    }
+
+    /// Area under the ROC curve, using one-vs-rest for multi-class problems.
+    /// The area is not defined (NaN) for a class that is always or never
+    /// the true class.
+    pub fn roc_auc(&self, average: &Average) -> Result<f64, MetricError> {
+        self.average(average, |scores, labels| {
+            let curve = Self::compute_roc_curve(scores, labels);
+            trapezoidal_area(&curve.false_positive_rate, &curve.true_positive_rate)
+        })
+    }
+
+    /// Average precision (area under the precision-recall curve), using
+    /// one-vs-rest for multi-class problems.
+    pub fn average_precision(&self, average: &Average) -> Result<f64, MetricError> {
+        self.average(average, |scores, labels| {
+            let curve = Self::compute_precision_recall_curve(scores, labels);
+            // sum of precisions weighted by the recall increase (step-wise area)
+            (1..curve.recall.len())
+                .map(|i| (curve.recall[i] - curve.recall[i - 1]) * curve.precision[i])
+                .sum()
+        })
+    }
+
+    // Apply the binary `score` function according to `average`. The class
+    // 0 of a binary problem (single target column) is the negative class.
+    fn average<F: Fn(&[f64], &[bool]) -> f64>(&self, average: &Average, score: F) -> Result<f64, MetricError> {
+        average.check_class(cmp::max(self.classes, 2))?;
+        let classes = self.binary_classes();
+        Ok(match average {
+            Average::Class(class) => {
+                let (scores, labels) = self.binary_scores(*class);
+                score(&scores, &labels)
+            }
+            Average::Macro => {
+                classes.iter().map(|class| {
+                    let (scores, labels) = self.binary_scores(*class);
+                    score(&scores, &labels)
+                }).sum::<f64>() / classes.len() as f64
+            }
+            Average::Micro => {
+                // every (observation, class) pair is considered as a binary prediction
+                let mut all_scores = Vec::new();
+                let mut all_labels = Vec::new();
+                for class in classes {
+                    let (scores, labels) = self.binary_scores(class);
+                    all_scores.extend(scores);
+                    all_labels.extend(labels);
+                }
+                score(&all_scores, &all_labels)
+            }
+            Average::Weighted => {
+                let mut weighted_sum = 0.0;
+                let mut total = 0;
+                for class in classes {
+                    let (scores, labels) = self.binary_scores(class);
+                    let support = labels.iter().filter(|l| **l).count();
+                    weighted_sum += score(&scores, &labels) * support as f64;
+                    total += support;
+                }
+                weighted_sum / total as f64
+            }
+        })
+    }
+
+    // Classes used for averaging: only the positive class for a binary problem
+    fn binary_classes(&self) -> Vec<usize> {
+        if self.classes == 1 { vec![1] } else { (0..self.classes).collect() }
+    }
+
+    // Get the predicted probability of `class` and whether `class` is the true class
+    // for each observation. For a binary problem, class 1 is the positive class.
+    fn binary_scores(&self, class: usize) -> (Vec<f64>, Vec<bool>) {
+        let scores = if self.classes == 1 {
+            self.probabilities.iter().map(|p| if class == 1 { *p } else { 1.0 - p }).collect()
+        } else {
+            self.probabilities.iter().skip(class).step_by(self.classes).copied().collect()
+        };
+        let labels = self.labels.iter().map(|l| *l == class).collect();
+        (scores, labels)
+    }
+
+    fn compute_roc_curve(scores: &[f64], labels: &[bool]) -> RocCurve {
+        let counts = cumulative_counts(scores, labels);
+        let (positives, negatives) = counts.last().map(|c| (c.1, c.2)).unwrap_or((0.0, 0.0));
+
+        // the curve starts at (0, 0) with an infinite threshold
+        let mut curve = RocCurve {
+            false_positive_rate: vec![0.0],
+            true_positive_rate: vec![0.0],
+            thresholds: vec![f64::INFINITY],
+        };
+        for (threshold, tp, fp) in counts {
+            curve.false_positive_rate.push(fp / negatives);
+            curve.true_positive_rate.push(tp / positives);
+            curve.thresholds.push(threshold);
+        }
+        curve
+    }
+
+    fn compute_precision_recall_curve(scores: &[f64], labels: &[bool]) -> PrecisionRecallCurve {
+        let counts = cumulative_counts(scores, labels);
+        let positives = counts.last().map(|c| c.1).unwrap_or(0.0);
+
+        // the curve starts at (recall = 0, precision = 1) with an infinite threshold
+        let mut curve = PrecisionRecallCurve {
+            precision: vec![1.0],
+            recall: vec![0.0],
+            thresholds: vec![f64::INFINITY],
+        };
+        for (threshold, tp, fp) in counts {
+            curve.precision.push(tp / (tp + fp));
+            curve.recall.push(tp / positives);
+            curve.thresholds.push(threshold);
+        }
+        curve
+    }
+}
+
+// Sort observations by decreasing score and return, for each distinct score,
+// the number of true positives and false positives when this score is
+// used as the decision threshold: (threshold, tp, fp)
+fn cumulative_counts(scores: &[f64], labels: &[bool]) -> Vec<(f64, f64, f64)> {
+    let mut indices: Vec<usize> = (0..scores.len()).collect();
+    indices.sort_by(|a, b| scores[*b].partial_cmp(&scores[*a]).unwrap_or(Ordering::Equal));
+
+    let mut result: Vec<(f64, f64, f64)> = Vec::new();
+    let (mut tp, mut fp) = (0.0, 0.0);
+    for (position, index) in indices.iter().enumerate() {
+        if labels[*index] { tp += 1.0 } else { fp += 1.0 }
+
+        // ties are grouped: only the last observation of a score adds a point
+        let is_last_of_score = position + 1 == indices.len() || scores[indices[position + 1]] != scores[*index];
+        if is_last_of_score {
+            result.push((scores[*index], tp, fp));
+        }
+    }
+    result
+}
+
+// Area under the curve (x, y) using the trapezoidal rule
+fn trapezoidal_area(x: &[f64], y: &[f64]) -> f64 {
+    (1..x.len())
+        .map(|i| (x[i] - x[i - 1]) * (y[i] + y[i - 1]) / 2.0)
+        .sum()
+}
@@ -0,0 +1,114 @@
+use crate::tensor::Tensor;
+
+/// Accumulate the statistics needed to compute regression metrics.
+/// Metrics are computed for each output (target column) and can be
+/// updated batch by batch.
+///
+/// Examples
+/// --------
+/// >>> let mut stats = metrics::regression::RegressionStats::new(outputs)
+/// >>> stats.update(&y_true, &y_pred)
+/// >>> stats.mae()
+/// >>> stats.r2_score()
+pub struct RegressionStats {
+    count: usize,
+    sum_abs_error: Vec<f64>,
+    sum_squared_error: Vec<f64>,
+    sum_abs_percentage_error: Vec<f64>,
+    // running means and sums of squared deviations from the mean (Welford),
+    // which keep their precision when the values have a large offset
+    mean_error: Vec<f64>,
+    error_deviations: Vec<f64>,
+    mean_true: Vec<f64>,
+    true_deviations: Vec<f64>,
+}
+
+impl RegressionStats {
+    /// Create empty statistics for `outputs` target columns.
+    pub fn new(outputs: usize) -> RegressionStats {
+        RegressionStats {
+            count: 0,
+            sum_abs_error: vec![0.0; outputs],
+            sum_squared_error: vec![0.0; outputs],
+            sum_abs_percentage_error: vec![0.0; outputs],
+            mean_error: vec![0.0; outputs],
+            error_deviations: vec![0.0; outputs],
+            mean_true: vec![0.0; outputs],
+            true_deviations: vec![0.0; outputs],
+        }
+    }
+
+    /// Add the observations of a new batch.
+    pub fn update(&mut self, y_true: &Tensor, y_pred: &Tensor) {
+        assert_eq!(y_true.shape, y_pred.shape);
+        assert_eq!(y_true.shape[1], self.mean_error.len(), "Number of outputs differs from the statistics size.");
+
+        for row in 0..y_true.shape[0] {
+            let n = (self.count + row + 1) as f64;
+            for col in 0..y_true.shape[1] {
+                let true_value = y_true.get_value(row, col);
+                let error = true_value - y_pred.get_value(row, col);
+
+                self.sum_abs_error[col] += error.abs();
+                self.sum_squared_error[col] += error * error;
+                // avoid division by zero like scikit-learn does
+                self.sum_abs_percentage_error[col] += error.abs() / true_value.abs().max(f64::EPSILON);
+                Self::welford_update(&mut self.mean_error[col], &mut self.error_deviations[col], error, n);
+                Self::welford_update(&mut self.mean_true[col], &mut self.true_deviations[col], true_value, n);
+            }
+        }
+        self.count += y_true.shape[0];
+    }
+
+    /// Mean absolute error of each output
+    pub fn mae(&self) -> Vec<f64> {
+        self.sum_abs_error.iter().map(|s| s / self.count as f64).collect()
+    }
+
+    /// Root mean squared error of each output
+    pub fn rmse(&self) -> Vec<f64> {
+        self.sum_squared_error.iter().map(|s| (s / self.count as f64).sqrt()).collect()
+    }
+
+    /// Mean absolute percentage error of each output (0.1 means 10%)
+    pub fn mape(&self) -> Vec<f64> {
+        self.sum_abs_percentage_error.iter().map(|s| s / self.count as f64).collect()
+    }
+
+    /// Coefficient of determination (R²) of each output
+    pub fn r2_score(&self) -> Vec<f64> {
+        (0..self.mean_true.len())
+            .map(|col| Self::one_minus_ratio(self.sum_squared_error[col], self.true_deviations[col]))
+            .collect()
+    }
+
+    /// Explained variance score of each output
+    pub fn explained_variance_score(&self) -> Vec<f64> {
+        // ratio of the variances of the errors and of the true values
+        (0..self.mean_true.len())
+            .map(|col| Self::one_minus_ratio(self.error_deviations[col], self.true_deviations[col]))
+            .collect()
+    }
+
+    // Add the `n`-th value to a running mean and sum of squared deviations
+    fn welford_update(mean: &mut f64, deviations: &mut f64, value: f64, n: f64) {
+        let delta = value - *mean;
+        *mean += delta / n;
+        *deviations += delta * (value - *mean);
+    }
+
+    // Compute 1 - numerator / denominator. A constant target (denominator = 0)
+    // gives 1 for a perfect prediction and 0 otherwise.
+    fn one_minus_ratio(numerator: f64, denominator: f64) -> f64 {
+        if denominator == 0.0 {
+            if numerator == 0.0 { 1.0 } else { 0.0 }
+        } else {
+            1.0 - numerator / denominator
+        }
+    }
+}
+
+/// Uniform average of the `values` computed for each output
+pub fn average(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
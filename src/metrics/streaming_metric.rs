@@ -0,0 +1,39 @@
+use std::fmt;
+
+use crate::metrics::MetricError;
+use crate::tensor::Tensor;
+
+/// Metric computed batch by batch. Implement this trait to use your own
+/// metric with `Metric::Custom`.
+///
+/// Examples
+/// --------
+/// >>> metric.reset()
+/// >>> metric.update(&y_true_batch_1, &y_pred_batch_1)
+/// >>> metric.update(&y_true_batch_2, &y_pred_batch_2)
+/// >>> metric.result()
+pub trait StreamingMetric {
+    /// Name used to report the metric
+    fn name(&self) -> String;
+    /// Check that the metric can be computed on `outputs` target columns,
+    /// before the observations of an evaluation are accumulated
+    fn check_outputs(&self, _outputs: usize) -> Result<(), MetricError> {
+        Ok(())
+    }
+    /// Accumulate the observations of a new batch
+    fn update(&mut self, y_true: &Tensor, y_pred: &Tensor);
+    /// Forget all the accumulated observations
+    fn reset(&mut self);
+    /// Value of the metric for all the accumulated observations
+    fn result(&self) -> f64;
+    /// Value of the metric for each output (target column), if relevant
+    fn result_per_output(&self) -> Vec<f64> {
+        vec![]
+    }
+}
+
+impl fmt::Debug for dyn StreamingMetric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
@@ -11,13 +11,26 @@ impl SGD {
 
 impl OptimizerStep for SGD {
     fn step(&self, layers: &mut [Box<dyn Layer>]) {
-        for layer in layers.iter_mut() {
+        // frozen layers keep their parameters
+        for layer in layers.iter_mut().filter(|layer| layer.is_trainable()) {
             for param in layer.get_params_list() {
                 let grad = layer.get_grad(&param).clone();
-                
+                let grad_rows = layer.get_grad_rows(&param);
+
                 let param_to_update = &mut *layer.get_param(&param);
-     
-                *param_to_update -= self.lr * grad;
+
+                match grad_rows {
+                    None => *param_to_update -= self.lr * grad,
+                    // only update the rows having a gradient
+                    Some(rows) => {
+                        let cols = param_to_update.shape[1];
+                        for (k, row) in rows.iter().enumerate() {
+                            for j in 0..cols {
+                                param_to_update.data[row * cols + j] -= self.lr * grad.data[k * cols + j];
+                            }
+                        }
+                    }
+                }
             }
         }
     }
@@ -1,45 +1,116 @@
-const KX: u32 = 123456789;
-const KY: u32 = 362436069;
-const KZ: u32 = 521288629;
-const KW: u32 = 88675123;
+//! Pseudo-random numbers used by the layers, the models and the datasets.
+//!
+//! `Rand` is the xoshiro256** generator. Its state is expanded from the seed
+//! with SplitMix64 so close seeds (0, 1, 2, ...) give unrelated streams.
+//! `SeedSequence` derives the seeds of independent streams (one per layer,
+//! one for batch shuffling, ...) from a single user seed.
+
+use std::f64::consts::PI;
+
+// Increment of the SplitMix64 generator (golden ratio)
+const GOLDEN_GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+
+// SplitMix64: return the next output and advance `state`
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(GOLDEN_GAMMA);
+    mix64(*state)
+}
+
+// Finalizer of SplitMix64: every bit of the input changes half of the output bits
+fn mix64(z: u64) -> u64 {
+    let z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    let z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
 
 pub struct Rand {
-    x: u32, y: u32, z: u32, w: u32
+    s: [u64; 4]
 }
 
-impl Rand{
+impl Rand {
     pub fn new(seed: u32) -> Rand {
-        Rand{
-            x: KX^seed, y: KY^seed,
-            z: KZ, w: KW
+        let mut state = seed as u64;
+        // SplitMix64 never outputs four zeros in a row, the only invalid state
+        Rand {
+            s: [splitmix64(&mut state), splitmix64(&mut state), splitmix64(&mut state), splitmix64(&mut state)]
         }
     }
 
-    // Xorshift 128, taken from German Wikipedia
-    pub fn rand(&mut self) -> u32 {
-        let t = self.x^self.x.wrapping_shl(11);
-        self.x = self.y; self.y = self.z; self.z = self.w;
-        self.w ^= self.w.wrapping_shr(19)^t^t.wrapping_shr(8);
-        return self.w;
+    // xoshiro256** by Blackman and Vigna
+    pub fn next_u64(&mut self) -> u64 {
+        let result = self.s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = self.s[1] << 17;
+
+        self.s[2] ^= self.s[0];
+        self.s[3] ^= self.s[1];
+        self.s[1] ^= self.s[2];
+        self.s[0] ^= self.s[3];
+        self.s[2] ^= t;
+        self.s[3] = self.s[3].rotate_left(45);
+
+        result
     }
 
     pub fn shuffle<T>(&mut self, a: &mut [T]) {
         if a.len()==0 {return;}
         let mut i = a.len()-1;
         while i>0 {
-            let j = (self.rand() as usize)%(i+1);
+            let j = self.below(i as u64 + 1) as usize;
             a.swap(i,j);
             i-=1;
         }
     }
 
-    pub fn rand_range(&mut self, a: i32, b: i32) -> i32 {
-        let m = (b-a+1) as u32;
-        return a+(self.rand()%m) as i32;
+    /// Return an integer uniformly distributed between 0 and `n` - 1
+    pub fn below(&mut self, n: u64) -> u64 {
+        // Lemire's method: multiply and reject the values causing a bias
+        let threshold = n.wrapping_neg() % n;
+        loop {
+            let product = self.next_u64() as u128 * n as u128;
+            if product as u64 >= threshold {
+                return (product >> 64) as u64;
+            }
+        }
     }
 
-    /// Return a f64 uniformly distributed between 0 and 1
+    /// Return a f64 uniformly distributed between 0 (included) and 1 (excluded)
     pub fn rand_float(&mut self) -> f64 {
-        (self.rand() as f64)/(<u32>::max_value() as f64)
+        // 53 random bits, the precision of a f64
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Return a f64 following the standard normal distribution (Box-Muller method)
+    pub fn standard_normal(&mut self) -> f64 {
+        // 1 - u is in ]0, 1] so its logarithm is finite
+        (-2.0 * (1.0 - self.rand_float()).ln()).sqrt() * (2.0 * PI * self.rand_float()).cos()
     }
-}
\ No newline at end of file
+}
+
+/// Node of a tree of seeds: each child is an independent and reproducible
+/// sequence, so a model derives a seed for each of its layers from its own
+/// seed and a layer may derive seeds for its sublayers.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SeedSequence {
+    key: u64
+}
+
+impl SeedSequence {
+    pub fn new(seed: u32) -> SeedSequence {
+        SeedSequence { key: mix64(seed as u64 ^ GOLDEN_GAMMA) }
+    }
+
+    /// Sequence of the child `index`
+    pub fn child(&self, index: u64) -> SeedSequence {
+        SeedSequence { key: mix64(self.key ^ mix64(index.wrapping_add(1).wrapping_mul(GOLDEN_GAMMA))) }
+    }
+
+    /// Seed of the `Rand` generator of this sequence
+    pub fn seed(&self) -> u32 {
+        (self.key >> 32) as u32
+    }
+}
+
+// Children of the seed sequence of a model or a dataset
+pub const LAYER_STREAMS: u64 = 0;
+pub const SHUFFLE_STREAMS: u64 = 1;
+pub const SPLIT_STREAM: u64 = 2;
@@ -0,0 +1,41 @@
+/// Penalties added to the loss to keep parameters or outputs small
+use crate::tensor::Tensor;
+
+/// Penalty of a tensor w, added to the loss reported by `fit` and whose
+/// gradient is added to the gradient of w.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Regularizer {
+    /// l1 * sum(|w|), drives small values to zero
+    L1(f64),
+    /// l2 * sum(w^2), also known as weight decay
+    L2(f64),
+    /// l1 * sum(|w|) + l2 * sum(w^2)
+    ElasticNet { l1: f64, l2: f64 },
+}
+
+impl Regularizer {
+    // (l1, l2) coefficients of the penalty
+    fn coefficients(&self) -> (f64, f64) {
+        match *self {
+            Regularizer::L1(l1) => (l1, 0.0),
+            Regularizer::L2(l2) => (0.0, l2),
+            Regularizer::ElasticNet { l1, l2 } => (l1, l2),
+        }
+    }
+
+    pub fn penalty(&self, tensor: &Tensor) -> f64 {
+        let (l1, l2) = self.coefficients();
+        tensor.data.iter().map(|w| l1 * w.abs() + l2 * w * w).sum()
+    }
+
+    /// Derivative of the penalty with respect to each value of `tensor`
+    /// (the derivative of |w| is taken as 0 for w = 0)
+    pub fn gradient(&self, tensor: &Tensor) -> Tensor {
+        let (l1, l2) = self.coefficients();
+        let data = tensor.data.iter().map(|w| {
+            let sign = if *w > 0.0 { 1.0 } else if *w < 0.0 { -1.0 } else { 0.0 };
+            l1 * sign + 2.0 * l2 * w
+        }).collect();
+        Tensor::new(data, tensor.shape.to_vec())
+    }
+}
@@ -1,29 +1,77 @@
 /// The Sequential model is a linear stack of layers.
-use std::cmp;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::fmt;
 
-use crate::layers::layer::Layer;
+use crate::layers::layer::{Layer, LearnableParams};
+use crate::constraints::Constraint;
 use crate::layers::*;
 use crate::layers::LayerEnum;
-use crate::metrics::Metric;
-use crate::metrics::*;
+use crate::metrics::{Evaluation, Metric, MetricError, MetricValue, streaming_metric::StreamingMetric};
 use crate::tensor::Tensor;
-use crate::dataset::{Dataset, RowType, ColumnType};
-use crate::{loss::loss::Loss, random::Rand, optimizers::optimizer::OptimizerStep, optimizers::sgd::SGD};
+use crate::dataset::{self, Dataset, RowType, ColumnType, BATCH_SIZE};
+use crate::{loss::loss::Loss, random::{SeedSequence, LAYER_STREAMS, SHUFFLE_STREAMS}, optimizers::optimizer::OptimizerStep, optimizers::sgd::SGD};
 use crate::loss::categorical_entropy::CategoricalEntropy;
 use crate::utils;
 
-struct Batch {
-    inputs: Tensor,
-    targets: Tensor
-}
+// Layer added by `Sequential::add_layer`, taken by `compile`
+type LayerSlot = Rc<RefCell<Option<Box<dyn Layer>>>>;
 
 pub struct Sequential {
     pub layers_enum: Vec<LayerEnum>,
     pub layers: Vec<Box<dyn Layer>>,
     loss: Box<dyn Loss>,
     optim: Box<dyn OptimizerStep>,
-    metrics: Vec<Metric>,
+    metrics: Vec<Box<dyn StreamingMetric>>,
     seed: u32,
+    // shuffle the training rows at each epoch of `fit`
+    shuffle: bool,
+    // epochs trained by all the calls to `fit`, each one is shuffled differently
+    trained_epochs: u64,
+    // input shape (without the batch dimension) given by the user
+    input_shape: Option<Vec<usize>>,
+    // input shape used to build the layers, None if not built yet
+    layers_input_shape: Option<Vec<usize>>,
+    // (layer index, parameter, constraint) applied after each optimizer step
+    constraints: Vec<(usize, LearnableParams, Constraint)>,
+    // indices of the layers frozen by `set_trainable`
+    frozen: Vec<usize>,
+    // (layer index, slot) of the layers added by `add_layer`, the slot is
+    // empty while the layer is in `layers`
+    instances: Vec<(usize, LayerSlot)>,
+}
+
+/// Error returned by `Sequential::compile` when the layers do not fit together
+#[derive(Debug, PartialEq)]
+pub enum ModelError {
+    /// The input shape of the layer at index `layer` cannot be inferred,
+    /// use `Sequential::set_input_shape`
+    UnknownInputShape { layer: usize, layer_type: String },
+    /// The layer at index `layer` does not accept the output of the previous layer
+    IncompatibleShape { layer: usize, layer_type: String, input_shape: Vec<usize>, message: String },
+    /// A constraint is added to a parameter the layer does not have
+    UnknownParameter { layer: usize, layer_type: String, param: LearnableParams },
+    /// The layer has parameters but does not implement `Layer::set_trainable`
+    NotFreezable { layer: usize, layer_type: String },
+}
+
+impl fmt::Display for ModelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModelError::UnknownInputShape { layer, layer_type } => {
+                write!(f, "Layer {} ({}): unknown input shape, use `Sequential::set_input_shape`.", layer, layer_type)
+            }
+            ModelError::IncompatibleShape { layer, layer_type, input_shape, message } => {
+                write!(f, "Layer {} ({}) with input shape {:?}: {}.", layer, layer_type, input_shape, message)
+            }
+            ModelError::UnknownParameter { layer, layer_type, param } => {
+                write!(f, "Layer {} ({}): no {:?} parameters to constrain.", layer, layer_type, param)
+            }
+            ModelError::NotFreezable { layer, layer_type } => {
+                write!(f, "Layer {} ({}): cannot be frozen, implement `Layer::set_trainable`.", layer, layer_type)
+            }
+        }
+    }
 }
 
 impl Sequential {
@@ -36,6 +84,13 @@ impl Sequential {
             optim: Box::new(SGD::new(0.02)),
             metrics: vec![],
             seed: 0,
+            shuffle: false,
+            trained_epochs: 0,
+            input_shape: None,
+            layers_input_shape: None,
+            constraints: vec![],
+            frozen: vec![],
+            instances: vec![],
         }
     }
 
@@ -44,12 +99,33 @@ impl Sequential {
         self.seed = s;
     }
 
+    /// Shuffle the training rows before splitting them into batches, with
+    /// another order at each epoch of `fit` derived from the seed of the model
+    pub fn set_shuffle(&mut self, shuffle: bool) {
+        self.shuffle = shuffle;
+    }
+
+    /// Set the shape of a sample (without the batch dimension). Rows of a
+    /// `Dataset` are reshaped to it. Must be called before `compile`.
+    pub fn set_input_shape(&mut self, shape: Vec<usize>) {
+        self.input_shape = Some(shape);
+    }
+
     /// Add a layer to the model
     pub fn add(&mut self, layer: LayerEnum) {
         self.layers_enum.push(layer);
     }
 
-
+    /// Add a custom layer instance to the model. The same instance is built
+    /// again when the model is compiled again, use `LayerEnum::Custom` with a
+    /// factory to get a new layer at each compilation.
+    pub fn add_layer(&mut self, layer: Box<dyn Layer>) {
+        let slot = Rc::new(RefCell::new(Some(layer)));
+        self.instances.push((self.layers_enum.len(), slot.clone()));
+        self.layers_enum.push(LayerEnum::Custom(Box::new(move |_seed| {
+            slot.borrow_mut().take().expect("The layer is given back by `compile` before being built.")
+        })));
+    }
 
     /// Get a summary of the model
     pub fn summary(&self) {
@@ -65,19 +141,25 @@ Layer (type)                 Output Shape              Param #
         for layer in &self.layers {
             let layer_info = layer.get_info(); 
             let layer_type = utils::fit_string_to_length(layer_info.layer_type, 29);
-            let output_shape_str = "(".to_string() + &layer_info.output_shape
+            // the first dimension is the batch size
+            let output_shape_str = "(None".to_string() + &layer_info.output_shape
                         .iter()
-                        .map(|u| u.to_string())
-                        .collect::<Vec<_>>()
-                        .join(", ") + ")";
+                        .map(|u| format!(", {}", u))
+                        .collect::<String>() + ")";
             let output_shape = utils::fit_string_to_length(output_shape_str, 26);
 
-            let trainable_param = utils::fit_string_to_length(layer_info.trainable_param.to_string(), 10);
-            
-            println!("{}{}{}", layer_type, output_shape, trainable_param);
+            let layer_param_count = layer_info.trainable_param + layer_info.non_trainable_param;
+            let layer_param = utils::fit_string_to_length(layer_param_count.to_string(), 10);
 
-            trainable_param_sum += layer_info.trainable_param;
-            non_trainable_param_sum += layer_info.non_trainable_param;
+            println!("{}{}{}", layer_type, output_shape, layer_param);
+
+            if layer.is_trainable() {
+                trainable_param_sum += layer_info.trainable_param;
+                non_trainable_param_sum += layer_info.non_trainable_param;
+            } else {
+                // parameters of frozen layers are not trained
+                non_trainable_param_sum += layer_param_count;
+            }
 
         }
         let total_params = trainable_param_sum + non_trainable_param_sum;
@@ -88,45 +170,302 @@ Layer (type)                 Output Shape              Param #
 
     }
 
-    pub fn compile<T: 'static + Loss, U: 'static + OptimizerStep>(&mut self, loss: T, optim: U, metrics: Vec<Metric>) {
+    pub fn compile<T: 'static + Loss, U: 'static + OptimizerStep>(&mut self, loss: T, optim: U, metrics: Vec<Metric>) -> Result<(), ModelError> {
         // Set options
         self.loss = Box::new(loss);
         self.optim = Box::new(optim);
-        self.metrics = metrics;
-
-        // Build layers
-        self.layers.clear();
-        for layer in &self.layers_enum {
-            self.layers.push(
-                match layer {
-                    LayerEnum::Dense { input_units, output_units } => {
-                        Box::new(dense::Dense::new(*input_units, *output_units, self.seed))
-                    }
-                    LayerEnum::ReLU => {
-                        Box::new(relu::ReLU::new())
-                    }
-                    LayerEnum::Softmax => {
-                        Box::new(softmax::Softmax::new())
-                    }
-                    LayerEnum::TanH => {
-                        Box::new(tanh::TanH::new())
-                    }
-                    LayerEnum::Sigmoid => {
-                        Box::new(sigmoid::Sigmoid::new())
-                    }
-                    LayerEnum::Dropout { prob } => {
-                        // Shape of Dropout is the same as last layer
-                        Box::new(dropout::Dropout::new(*prob, self.seed))
-                    }
+        self.metrics = metrics.into_iter().map(Metric::into_streaming).collect();
+
+        // The input shape is given by `set_input_shape` or by the first layer,
+        // otherwise the layers are built at the beginning of `fit`
+        let mut shape = match (&self.input_shape, self.layers_enum.first()) {
+            (Some(input_shape), _) => Some(input_shape.to_vec()),
+            (None, Some(LayerEnum::Dense { input_units: Some(units), .. })) => Some(vec![*units]),
+            _ => None,
+        };
+        self.layers_input_shape = shape.clone();
+
+        // Give the layers added by `add_layer` back to their slots
+        let mut previous_layers: Vec<Option<Box<dyn Layer>>> = self.layers.drain(..).map(Some).collect();
+        for (index, slot) in &self.instances {
+            if let Some(layer) = previous_layers.get_mut(*index).and_then(Option::take) {
+                *slot.borrow_mut() = Some(layer);
+            }
+        }
+
+        // Build layers, each one with its own random stream
+        let seeds = SeedSequence::new(self.seed).child(LAYER_STREAMS);
+        for (index, layer) in self.layers_enum.iter().enumerate() {
+            let seed = seeds.child(index as u64).seed();
+            let mut built_layer = Sequential::create_layer(layer, index, &shape, seed)?;
+
+            // Propagate the shapes layer by layer
+            let output_shape = shape.map(|input_shape| built_layer.build(&input_shape).map_err(|message| ModelError::IncompatibleShape {
+                layer: index,
+                layer_type: built_layer.get_info().layer_type,
+                input_shape,
+                message,
+            }));
+            // kept even if the build fails so its slot gets it back
+            self.layers.push(built_layer);
+            shape = output_shape.transpose()?;
+        }
+
+        for layer in &self.frozen {
+            let built_layer = &mut self.layers[*layer];
+            built_layer.set_trainable(false);
+            if built_layer.is_trainable() && !built_layer.get_params_list().is_empty() {
+                return Err(ModelError::NotFreezable { layer: *layer, layer_type: built_layer.get_info().layer_type });
+            }
+        }
+
+        for (layer, param, _) in &self.constraints {
+            if !self.layers[*layer].get_params_list().contains(param) {
+                return Err(ModelError::UnknownParameter {
+                    layer: *layer,
+                    layer_type: self.layers[*layer].get_info().layer_type,
+                    param: param.clone(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    // Create the layer described by `layer` (at `index` in the model) for
+    // inputs of shape `shape`, None if the shape is not known yet
+    pub(crate) fn create_layer(layer: &LayerEnum, index: usize, shape: &Option<Vec<usize>>, seed: u32) -> Result<Box<dyn Layer>, ModelError> {
+        let layer: Box<dyn Layer> = match layer {
+            LayerEnum::Dense { input_units, output_units, kernel_initializer, bias_initializer, kernel_regularizer, bias_regularizer } => {
+                // infer the number of input units from the previous layer
+                let input_units = match (input_units, shape) {
+                    (Some(units), _) => *units,
+                    (None, Some(shape)) if shape.len() == 1 => shape[0],
+                    (None, Some(shape)) => return Err(ModelError::IncompatibleShape {
+                        layer: index,
+                        layer_type: "Dense".to_string(),
+                        input_shape: shape.to_vec(),
+                        message: "expected a 1D input shape (add a Flatten layer)".to_string(),
+                    }),
+                    (None, None) => return Err(ModelError::UnknownInputShape {
+                        layer: index,
+                        layer_type: "Dense".to_string(),
+                    }),
+                };
+                let mut dense = dense::Dense::new(input_units, *output_units, seed)
+                    .with_kernel_initializer(*kernel_initializer)
+                    .with_bias_initializer(*bias_initializer);
+                if let Some(regularizer) = kernel_regularizer {
+                    dense = dense.with_kernel_regularizer(*regularizer);
                 }
-            );
+                if let Some(regularizer) = bias_regularizer {
+                    dense = dense.with_bias_regularizer(*regularizer);
+                }
+                Box::new(dense)
+            }
+            LayerEnum::ReLU => {
+                Box::new(relu::ReLU::new())
+            }
+            LayerEnum::Softmax => {
+                Box::new(softmax::Softmax::new())
+            }
+            LayerEnum::TanH => {
+                Box::new(tanh::TanH::new())
+            }
+            LayerEnum::Sigmoid => {
+                Box::new(sigmoid::Sigmoid::new())
+            }
+            LayerEnum::Dropout { prob } => {
+                // Shape of Dropout is the same as last layer
+                Box::new(dropout::Dropout::new(*prob, seed))
+            }
+            LayerEnum::LeakyReLU { slope } => {
+                Box::new(activation::Activation::new(activation::ActivationFn::LeakyReLU { slope: *slope }))
+            }
+            LayerEnum::ELU { alpha } => {
+                Box::new(activation::Activation::new(activation::ActivationFn::ELU { alpha: *alpha }))
+            }
+            LayerEnum::SELU => {
+                Box::new(activation::Activation::new(activation::ActivationFn::SELU))
+            }
+            LayerEnum::GELU => {
+                Box::new(activation::Activation::new(activation::ActivationFn::GELU))
+            }
+            LayerEnum::Swish { beta } => {
+                Box::new(activation::Activation::new(activation::ActivationFn::Swish { beta: *beta }))
+            }
+            LayerEnum::Softplus => {
+                Box::new(activation::Activation::new(activation::ActivationFn::Softplus))
+            }
+            LayerEnum::Mish => {
+                Box::new(activation::Activation::new(activation::ActivationFn::Mish))
+            }
+            LayerEnum::PReLU { initial_slope, data_format } => {
+                Box::new(prelu::PReLU::new(*initial_slope, *data_format))
+            }
+            LayerEnum::ActivityRegularization { regularizer } => {
+                Box::new(activity_regularization::ActivityRegularization::new(*regularizer))
+            }
+            LayerEnum::Conv2D { input_channels, filters, kernel_size, stride, padding, dilation, data_format } => {
+                Box::new(conv2d::Conv2D::new(*input_channels, *filters, *kernel_size, seed)
+                    .with_stride(*stride)
+                    .with_padding(*padding)
+                    .with_dilation(*dilation)
+                    .with_data_format(*data_format))
+            }
+            LayerEnum::MaxPool2D { pool_size, stride, padding, data_format } => {
+                Box::new(pooling2d::Pool2D::new(pooling2d::PoolMode::Max, *pool_size)
+                    .with_stride(*stride)
+                    .with_padding(*padding)
+                    .with_data_format(*data_format))
+            }
+            LayerEnum::AvgPool2D { pool_size, stride, padding, data_format } => {
+                Box::new(pooling2d::Pool2D::new(pooling2d::PoolMode::Average, *pool_size)
+                    .with_stride(*stride)
+                    .with_padding(*padding)
+                    .with_data_format(*data_format))
+            }
+            LayerEnum::GlobalMaxPooling2D { data_format } => {
+                Box::new(pooling2d::GlobalPool2D::new(pooling2d::PoolMode::Max, *data_format))
+            }
+            LayerEnum::GlobalAveragePooling2D { data_format } => {
+                Box::new(pooling2d::GlobalPool2D::new(pooling2d::PoolMode::Average, *data_format))
+            }
+            LayerEnum::Conv1D { input_channels, filters, kernel_size, stride, padding, dilation } => {
+                Box::new(conv1d::Conv1D::new(*input_channels, *filters, *kernel_size, seed)
+                    .with_stride(*stride)
+                    .with_padding(*padding)
+                    .with_dilation(*dilation))
+            }
+            LayerEnum::MaxPool1D { pool_size, stride, padding } => {
+                Box::new(pooling1d::Pool1D::new(pooling2d::PoolMode::Max, *pool_size)
+                    .with_stride(*stride)
+                    .with_padding(*padding))
+            }
+            LayerEnum::AvgPool1D { pool_size, stride, padding } => {
+                Box::new(pooling1d::Pool1D::new(pooling2d::PoolMode::Average, *pool_size)
+                    .with_stride(*stride)
+                    .with_padding(*padding))
+            }
+            LayerEnum::Flatten => {
+                Box::new(flatten::Flatten::new())
+            }
+            LayerEnum::Reshape { target_shape } => {
+                Box::new(reshape::Reshape::new(target_shape.to_vec()))
+            }
+            LayerEnum::BatchNorm { momentum, epsilon, data_format } => {
+                Box::new(batch_norm::BatchNorm::new(*momentum, *epsilon, *data_format))
+            }
+            LayerEnum::LayerNorm { epsilon } => {
+                Box::new(layer_norm::LayerNorm::new(layer_norm::NormMode::Layer, *epsilon))
+            }
+            LayerEnum::RMSNorm { epsilon } => {
+                Box::new(layer_norm::LayerNorm::new(layer_norm::NormMode::RMS, *epsilon))
+            }
+            LayerEnum::Embedding { vocab_size, dim } => {
+                Box::new(embedding::Embedding::new(*vocab_size, *dim, seed))
+            }
+            LayerEnum::SimpleRNN { .. } | LayerEnum::LSTM { .. } | LayerEnum::GRU { .. } => {
+                Box::new(Sequential::recurrent_layer(layer, index, shape, seed)?)
+            }
+            LayerEnum::Bidirectional { layer: inner, merge_mode } => {
+                let seeds = SeedSequence::new(seed);
+                let forward = Sequential::recurrent_layer(inner, index, shape, seeds.child(0).seed())?;
+                let backward = Sequential::recurrent_layer(inner, index, shape, seeds.child(1).seed())?;
+                Box::new(bidirectional::Bidirectional::new(forward, backward, *merge_mode))
+            }
+            LayerEnum::MultiHeadAttention { model_dim, heads, causal, mask_padding } => {
+                let model_dim = Sequential::sequence_features(*model_dim, index, shape, "MultiHeadAttention")?;
+                Box::new(attention::MultiHeadAttention::new(model_dim, *heads, seed)
+                    .with_causal_mask(*causal)
+                    .with_padding_mask(*mask_padding))
+            }
+            LayerEnum::TransformerEncoderBlock { model_dim, heads, feed_forward_dim, causal, mask_padding } => {
+                let model_dim = Sequential::sequence_features(*model_dim, index, shape, "TransformerEncoderBlock")?;
+                Box::new(transformer::TransformerEncoderBlock::new(model_dim, *heads, *feed_forward_dim, seed)
+                    .with_causal_mask(*causal)
+                    .with_padding_mask(*mask_padding))
+            }
+            LayerEnum::Custom(factory) => factory(seed),
+        };
+        Ok(layer)
+    }
+
+    // Create the recurrent layer described by `layer` (at `index` in the model)
+    // for inputs of shape `shape` [time, features]
+    fn recurrent_layer(layer: &LayerEnum, index: usize, shape: &Option<Vec<usize>>, seed: u32) -> Result<recurrent::Recurrent, ModelError> {
+        let (cell, input_features, units, return_sequences, initial_state) = match layer {
+            LayerEnum::SimpleRNN { input_features, units, return_sequences, initial_state } => (recurrent::CellType::SimpleRNN, input_features, units, return_sequences, initial_state),
+            LayerEnum::LSTM { input_features, units, return_sequences, initial_state } => (recurrent::CellType::LSTM, input_features, units, return_sequences, initial_state),
+            LayerEnum::GRU { input_features, units, return_sequences, initial_state } => (recurrent::CellType::GRU, input_features, units, return_sequences, initial_state),
+            _ => panic!("Bidirectional only wraps SimpleRNN, LSTM or GRU layers."),
+        };
+
+        let input_features = Sequential::sequence_features(*input_features, index, shape, &format!("{:?}", cell))?;
+
+        let layer = recurrent::Recurrent::new(cell, input_features, *units, seed).with_return_sequences(*return_sequences);
+        match initial_state {
+            None => Ok(layer),
+            Some(states) if states.len() == cell.states() && states.iter().all(|state| state.shape.len() == 2 && state.shape[1] == *units) => {
+                Ok(layer.with_initial_state(states.to_vec()))
+            }
+            Some(_) => Err(ModelError::IncompatibleShape {
+                layer: index,
+                layer_type: format!("{:?}", cell),
+                input_shape: shape.clone().unwrap_or_default(),
+                message: format!("expected {} initial state tensors of shape [batch, {}]", cell.states(), units),
+            }),
+        }
+    }
+
+    // Number of features of [time, features] inputs given by the user or
+    // inferred from the output shape `shape` of the previous layer
+    fn sequence_features(features: Option<usize>, index: usize, shape: &Option<Vec<usize>>, layer_type: &str) -> Result<usize, ModelError> {
+        match (features, shape) {
+            (Some(features), _) => Ok(features),
+            (None, Some(shape)) if shape.len() == 2 => Ok(shape[1]),
+            (None, Some(shape)) => Err(ModelError::IncompatibleShape {
+                layer: index,
+                layer_type: layer_type.to_string(),
+                input_shape: shape.to_vec(),
+                message: "expected an input shape [time, features]".to_string(),
+            }),
+            (None, None) => Err(ModelError::UnknownInputShape {
+                layer: index,
+                layer_type: layer_type.to_string(),
+            }),
+        }
+    }
+
+    // Build each layer with the output shape of the previous one
+    fn build(&mut self, input_shape: Vec<usize>) -> Result<(), ModelError> {
+        let mut shape = input_shape.to_vec();
+        for (index, layer) in self.layers.iter_mut().enumerate() {
+            shape = layer.build(&shape).map_err(|message| ModelError::IncompatibleShape {
+                layer: index,
+                layer_type: layer.get_info().layer_type,
+                input_shape: shape.to_vec(),
+                message,
+            })?;
         }
+        self.layers_input_shape = Some(input_shape);
+        Ok(())
     }
 
     // Return the last layer output given an input
     fn forward_propagation(&mut self, input: Tensor, train: bool) -> Tensor {
         // Compute activations of all network layers by applying them sequentially.
 
+        // rows of a dataset are flat, give them the input shape of the model
+        let input = match &self.layers_input_shape {
+            Some(shape) if shape.len() > 1 && input.shape.len() == 2 => {
+                let mut input_shape = vec![input.shape[0]];
+                input_shape.extend(shape);
+                Tensor::new(input.data, input_shape)
+            }
+            _ => input,
+        };
+
         let mut activations: Vec<Tensor> = Vec::new();
         activations.push(input);
         
@@ -152,69 +491,90 @@ Layer (type)                 Output Shape              Param #
         gradients.last().unwrap().clone()
     }
 
-    /// Return a vector containing all batch
-    /// if `shuffle` is set to true, batches are randomized
-    fn get_batches(&mut self, dataset: &Dataset, batch_size: usize, shuffle: bool) -> Vec<Batch> {
-        let x_train = dataset.get_tensor(RowType::Train, ColumnType::Feature); 
-        let y_train = dataset.get_tensor(RowType::Train, ColumnType::Target);
-        
-        let mut indices = (0..x_train.shape[0]).collect::<Vec<usize>>();
+    /// Constrain the parameter `param` of the layer at index `layer` after
+    /// each optimizer step, e.g. `Constraint::NonNeg` on the `Weights` of
+    /// every `Dense` layer makes the model monotone for ReLU activations.
+    pub fn add_constraint(&mut self, layer: usize, param: LearnableParams, constraint: Constraint) {
+        assert!(layer < self.layers_enum.len(), "Cannot constrain the layer {}: the model has {} layers.", layer, self.layers_enum.len());
+        self.constraints.push((layer, param, constraint));
+    }
 
-        if shuffle {
-            let mut rand = Rand::new(self.seed);
-            rand.shuffle(&mut indices[..]);
-            self.seed += 1;
+    /// Freeze (`false`) or unfreeze the layer at index `layer`: optimizers
+    /// do not update the parameters of frozen layers, e.g. the pretrained
+    /// base of a network whose head is fine-tuned. The flag is kept when
+    /// the model is compiled again.
+    pub fn set_trainable(&mut self, layer: usize, trainable: bool) {
+        assert!(layer < self.layers_enum.len(), "Cannot freeze the layer {}: the model has {} layers.", layer, self.layers_enum.len());
+        self.frozen.retain(|index| *index != layer);
+        if !trainable {
+            self.frozen.push(layer);
         }
-
-        let mut result = Vec::new();
-
-        for batch_index in (0..x_train.shape[0]).rev().skip(batch_size - 1).step_by(batch_size).rev() {
-            let batch_indices: &[usize] = &indices[batch_index..batch_index + batch_size];
-
-            let x_batch = x_train.get_rows(batch_indices);
-            let y_batch = y_train.get_rows(batch_indices);
-
-            result.push(Batch {inputs: x_batch, targets: y_batch});
+        // the layer is already built
+        if let Some(built_layer) = self.layers.get_mut(layer) {
+            built_layer.set_trainable(trainable);
         }
-    
-        result
     }
 
     /// Use this function to train the model on x_train with target y_train.
     /// Set `verbose` to true to see debugging and training information.
     pub fn fit(&mut self, dataset: &Dataset, epochs: u32, verbose: bool) {
 
-        // TODO: Check model architecture (input_unit == x_train.len(),
-        // output_unit_l == input_unit_l+1, output_unit_l_n = y_train.len()) and display message here
-        
-        // auto batch size : TODO improve it
-        let batch_size = cmp::min(dataset.count_row_type(&RowType::Train), 128);
+        // Check the model architecture against the dataset
+        let features = dataset.get_number_features();
+        if self.layers_input_shape.is_none() {
+            if let Err(error) = self.build(vec![features]) {
+                panic!("{}", error);
+            }
+        }
+        let input_size: usize = self.layers_input_shape.as_ref().unwrap().iter().product();
+        assert_eq!(input_size, features,
+            "The model expects {} features per row but the dataset has {}.", input_size, features);
+        if let Some(layer) = self.layers.last() {
+            let output_size: usize = layer.get_info().output_shape.iter().product();
+            assert_eq!(output_size, dataset.get_number_targets(),
+                "The model outputs {} values per row but the dataset has {} targets.", output_size, dataset.get_number_targets());
+        }
+
+        let x_train = dataset.get_tensor(RowType::Train, ColumnType::Feature);
+        let y_train = dataset.get_tensor(RowType::Train, ColumnType::Target);
     
         for epoch in 0..epochs {
             let mut epoch_loss = 0.0;
 
-            let batches = self.get_batches(dataset, batch_size, false);
+            // each epoch of every call to `fit` is shuffled differently
+            let shuffle = self.shuffle.then(|| SeedSequence::new(self.seed).child(SHUFFLE_STREAMS).child(self.trained_epochs).seed());
+            let batches = dataset::batch_indexes(x_train.shape[0], shuffle);
+            self.trained_epochs += 1;
             let batches_len = batches.len() as f64;
 
-            for batch in batches {
+            for batch in &batches {
                 // Train our network on a given batch (containing features & targets).
                 // We first need to run forward to get all layer activations.
                 // Then we can run layer.backward going from last to first layer.
+                let targets = y_train.get_rows(batch);
 
                 // Forward pass to get the predicted value
-                let predicted = self.forward_propagation(batch.inputs, true);
+                let predicted = self.forward_propagation(x_train.get_rows(batch), true);
                 
-                // compute loss and average loss gradient
-                epoch_loss += self.loss.compute_loss(&batch.targets, &predicted);
+                // compute loss (with the penalties of the regularizers) and average loss gradient
+                epoch_loss += self.loss.compute_loss(&targets, &predicted);
+                epoch_loss += self.layers.iter().map(|layer| layer.get_regularization_loss()).sum::<f64>();
                 
                 // Compute the loss gradient
-                let loss_grad = self.loss.compute_loss_grad(&batch.targets, &predicted);
+                let loss_grad = self.loss.compute_loss_grad(&targets, &predicted);
                 
                 // Compute layers gradient
                 self.backward_propagation(loss_grad);
 
                 // Update parameters according to the Optimizer specified
                 self.optim.step(&mut self.layers);
+                // frozen layers keep their parameters unchanged
+                for (layer, param, constraint) in &self.constraints {
+                    let layer = &mut self.layers[*layer];
+                    if layer.is_trainable() {
+                        constraint.apply(layer.get_param(param));
+                    }
+                }
             }
 
             if verbose {
@@ -222,44 +582,57 @@ Layer (type)                 Output Shape              Param #
                 println!("Train loss: {:.4}", epoch_loss/ batches_len);
 
                 if dataset.count_row_type(&RowType::Test) > 0 {
-                    let test_predictions = self.predict_tensor(dataset.get_tensor(RowType::Test, ColumnType::Feature));
-                    let test_true_values = &dataset.get_tensor(RowType::Test, ColumnType::Target);
-                    assert_eq!(test_predictions.shape, test_true_values.shape, "Something wrong happened... o_O");
-                    let test_loss = self.loss.compute_loss(test_true_values, &test_predictions);
-                    println!("Test loss:  {:.4}", test_loss);
-
-                    for metric in &self.metrics {
-                        let cm = confusion_matrix::ConfusionMatrix::new(
-                            test_true_values.clone(), 
-                            test_predictions.clone()
-                        );
-                        match metric {
-                            Metric::Accuracy => {
-                                let acc_score = cm.accuracy_score();
-                                println!("Accuracy: {:.2}%", acc_score * 100.0);
-                            }
-                            Metric::Recall => {
-                                let class = 1;
-                                let recall_score = cm.recall_score(class);
-                                println!("Recall: {:.2}%", recall_score * 100.0);
-                            }
-                            Metric::Precision => {
-                                let class = 1;
-                                let precision_score = cm.precision_score(class);
-                                println!("Precision: {:.2}%", precision_score * 100.0);
-                            }
-                            Metric::F1 => {
-                                let class = 1;
-                                let f1_score = cm.f1_score(class);
-                                println!("F1 Score: {:.2}%", f1_score * 100.0);
-                            }
+                    match self.evaluate(dataset, RowType::Test) {
+                        Ok(evaluation) => {
+                            println!("Test loss:  {:.4}", evaluation.loss);
+                            evaluation.print_metrics();
                         }
+                        Err(error) => println!("{}", error),
                     }
                 }
             }
         }
     }
 
+    /// Evaluate the model on the rows of `dataset` having the `row_type`
+    /// specified. Return the loss and the value of every metric specified
+    /// in `compile`, or an error if a metric cannot be computed on the
+    /// targets of `dataset` (e.g. `Average::Class` of an unknown class).
+    /// Rows are processed by batch so the whole dataset is never predicted
+    /// at once.
+    pub fn evaluate(&mut self, dataset: &Dataset, row_type: RowType) -> Result<Evaluation, MetricError> {
+        let rows = dataset.count_row_type(&row_type);
+        assert!(rows > 0, "Cannot evaluate the model without any row of type {:?}.", row_type);
+
+        let mut loss_sum = 0.0;
+        for metric in self.metrics.iter_mut() {
+            metric.check_outputs(dataset.get_number_targets())?;
+            metric.reset();
+        }
+
+        for (inputs, true_values) in dataset.batches(row_type, BATCH_SIZE) {
+            let predictions = self.predict_tensor(inputs);
+            assert_eq!(predictions.shape, true_values.shape, "Something wrong happened... o_O");
+
+            // the loss is averaged over the batch so we weight it by the batch size
+            loss_sum += self.loss.compute_loss(&true_values, &predictions) * true_values.shape[0] as f64;
+            for metric in self.metrics.iter_mut() {
+                metric.update(&true_values, &predictions);
+            }
+        }
+
+        let metrics = self.metrics.iter().map(|metric| MetricValue {
+            name: metric.name(),
+            value: metric.result(),
+            per_output: metric.result_per_output(),
+        }).collect();
+
+        Ok(Evaluation {
+            loss: loss_sum / rows as f64,
+            metrics,
+        })
+    }
+
     pub fn predict(&mut self, input: &Vec<f64>) -> Tensor {
         let tensor_input = Tensor::new(input.to_vec(), vec![1, input.to_vec().len()]);
         self.predict_tensor(tensor_input)
@@ -1,11 +1,8 @@
 // Implement basic tensor structure
 
-use crate::random::Rand;
-
 use std::cmp;
 use std::fmt;
 use std::ops::{Add, Index, Mul, Sub, SubAssign, Div};
-use std::f64::consts::PI;
 
 #[derive(Clone)]
 pub struct Tensor {
@@ -35,52 +32,6 @@ impl Tensor {
         }
     }
 
-    /// Creates a Tensor filled with uniformly distributed random values
-    /// between -1 and +1 with the `shape` specified.
-    pub fn random(shape: Vec<usize>, seed: u32) -> Tensor {
-        let mut rng = Rand::new(seed);
-
-        let number_values = shape.iter().product();
-        let data: Vec<f64> = (0..number_values).map(|_| rng.rand_float()).collect();
-        Tensor { data, shape }
-    }
-
-    /// Generates a Tensor filled with random values following a normal distribution
-    /// with parameters mu and sigma specified (mean/stdev)
-    pub fn random_normal(shape: Vec<usize>, mean: f64, stdev: f64, seed: u32) -> Tensor {
-        // We use the Box-Muller method to generate random normal values
-        // Formula: sqrt(-2*ln(rand()))*cos(2*Pi*rand()) * stdev + mean
-        let mut rng = Rand::new(seed);
-
-        let number_values = shape.iter().product();
-
-        let data: Vec<f64> = (0..number_values).map(|_| (
-            // formula
-            ((-2.0 * rng.rand_float().ln()).sqrt() * (2.0 * PI * rng.rand_float()).cos()) * stdev + mean
-        )).collect();
-
-        Tensor { data, shape }
-    }
-
-    pub fn mask(shape: &Vec<usize>, prob: f64, seed: u32) -> Tensor {
-        let mut result = vec![];
-        let number_values = shape.iter().product();
-
-        for i in 0..number_values {
-            let t = (prob * number_values as f64) as usize;
-            if i < t {
-                result.push(0.0);
-            } else {
-                result.push(1.0 / (1.0-prob));
-            }
-        }
-        let mut rng = Rand::new(seed);
-        rng.shuffle(&mut result[..]);
-
-        Tensor::new(result, shape.to_vec())
-    }
-
-
     /// Creates new matrix based on the transposed `self` Tensor
     pub fn get_transpose(&self) -> Tensor {
         let mut data = Vec::with_capacity(self.data.len());
@@ -97,6 +48,38 @@ impl Tensor {
         }
     }
 
+    /// Creates new Tensor whose axes are the axes of `self` reordered
+    /// according to `axes` (e.g. [0, 2, 3, 1] converts NCHW to NHWC)
+    pub fn get_permuted(&self, axes: &[usize]) -> Tensor {
+        assert_eq!(axes.len(), self.shape.len(), "Permutation must list every axis.");
+
+        let shape: Vec<usize> = axes.iter().map(|a| self.shape[*a]).collect();
+
+        // strides of `self` for each axis of the new tensor
+        let mut strides = vec![1; self.shape.len()];
+        for i in (0..self.shape.len().saturating_sub(1)).rev() {
+            strides[i] = strides[i + 1] * self.shape[i + 1];
+        }
+        let strides: Vec<usize> = axes.iter().map(|a| strides[*a]).collect();
+
+        let mut data = Vec::with_capacity(self.data.len());
+        let mut index = vec![0; shape.len()];
+        for _ in 0..self.data.len() {
+            data.push(self.data[index.iter().zip(strides.iter()).map(|(i, s)| i * s).sum::<usize>()]);
+
+            // increment the multi-dimensional index (last axis first)
+            for axis in (0..shape.len()).rev() {
+                index[axis] += 1;
+                if index[axis] < shape[axis] {
+                    break;
+                }
+                index[axis] = 0;
+            }
+        }
+
+        Tensor { data, shape }
+    }
+
     /// Compute the mean of the matrix along the `axis` specified.
     /// 0 = along the column, 1 = along the row
     pub fn get_mean(&self, axis: usize) -> Tensor {
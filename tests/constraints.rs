@@ -0,0 +1,141 @@
+#[cfg(test)]
+mod constraints_tests {
+    use newron::constraints::Constraint;
+    use newron::dataset::Dataset;
+    use newron::graph::{Graph, GraphError};
+    use newron::layers::LayerEnum::{self, *};
+    use newron::layers::layer::LearnableParams;
+    use newron::loss::mse::MSE;
+    use newron::optimizers::sgd::SGD;
+    use newron::sequential::{Sequential, ModelError};
+    use newron::tensor::Tensor;
+
+    // Norms of the columns of a 2D tensor
+    fn column_norms(tensor: &Tensor) -> Vec<f64> {
+        let (rows, cols) = (tensor.shape[0], tensor.shape[1]);
+        (0..cols).map(|j| (0..rows).map(|i| tensor.data[i * cols + j].powi(2)).sum::<f64>().sqrt()).collect()
+    }
+
+    fn assert_close(a: &[f64], b: &[f64]) {
+        assert!(a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-12), "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn test_constraints() {
+        // columns of norm 5, 0.5 and 0
+        let weights = Tensor::new(vec![
+            3.0, 0.5, 0.0,
+            -4.0, 0.0, 0.0], vec![2, 3]);
+
+        let mut tensor = weights.clone();
+        Constraint::MaxNorm { max_value: 2.0, axis: 0 }.apply(&mut tensor);
+        assert_close(&column_norms(&tensor), &[2.0, 0.5, 0.0]);
+        assert_close(&tensor.data[..1], &[1.2]);
+
+        let mut tensor = weights.clone();
+        Constraint::UnitNorm { axis: 0 }.apply(&mut tensor);
+        assert_close(&column_norms(&tensor), &[1.0, 1.0, 0.0]);
+
+        // rows along the last axis
+        let mut tensor = weights.clone();
+        Constraint::UnitNorm { axis: 1 }.apply(&mut tensor);
+        assert_close(&tensor.data, &[3.0 / 9.25_f64.sqrt(), 0.5 / 9.25_f64.sqrt(), 0.0, -1.0, 0.0, 0.0]);
+
+        let mut tensor = weights.clone();
+        Constraint::MinMaxNorm { min_value: 1.0, max_value: 3.0, rate: 1.0, axis: 0 }.apply(&mut tensor);
+        assert_close(&column_norms(&tensor), &[3.0, 1.0, 0.0]);
+
+        let mut tensor = weights.clone();
+        Constraint::MinMaxNorm { min_value: 1.0, max_value: 3.0, rate: 0.5, axis: 0 }.apply(&mut tensor);
+        assert_close(&column_norms(&tensor), &[4.0, 0.75, 0.0]);
+
+        let mut tensor = weights.clone();
+        Constraint::NonNeg.apply(&mut tensor);
+        assert_eq!(tensor, Tensor::new(vec![3.0, 0.5, 0.0, 0.0, 0.0, 0.0], vec![2, 3]));
+    }
+
+    #[test]
+    fn test_fit_with_constraints() {
+        // the target decreases with the first feature
+        let data = (0..40).map(|i| {
+            let x = i as f64 / 40.0;
+            vec![x, 1.0 - x, 1.0 - 2.0 * x]
+        }).collect();
+        let dataset = Dataset::from_raw_data(data).unwrap();
+
+        let mut model = Sequential::new();
+        model.add(LayerEnum::dense(Some(2), 4));
+        model.add(ReLU);
+        model.add(LayerEnum::dense(None, 1));
+        model.add_constraint(0, LearnableParams::Weights, Constraint::MaxNorm { max_value: 0.5, axis: 0 });
+        model.add_constraint(2, LearnableParams::Weights, Constraint::NonNeg);
+        model.compile(MSE{}, SGD::new(0.01), vec![]).unwrap();
+        model.fit(&dataset, 20, false);
+
+        let hidden = model.layers[0].get_param(&LearnableParams::Weights).clone();
+        assert!(column_norms(&hidden).iter().all(|norm| *norm <= 0.5 + 1e-12));
+        assert!(model.layers[2].get_param(&LearnableParams::Weights).data.iter().all(|w| *w >= 0.0));
+
+        // ReLU has no parameters
+        let mut model = Sequential::new();
+        model.add(LayerEnum::dense(Some(2), 1));
+        model.add(ReLU);
+        model.add_constraint(1, LearnableParams::Weights, Constraint::NonNeg);
+        let error = model.compile(MSE{}, SGD::new(0.01), vec![]).unwrap_err();
+        assert_eq!(error, ModelError::UnknownParameter { layer: 1, layer_type: "ReLU".to_string(), param: LearnableParams::Weights });
+        assert_eq!(error.to_string(), "Layer 1 (ReLU): no Weights parameters to constrain.");
+    }
+
+    #[test]
+    fn test_frozen_constrained_layer() {
+        let data = (0..20).map(|i| vec![i as f64 / 20.0, 1.0]).collect();
+        let dataset = Dataset::from_raw_data(data).unwrap();
+
+        let mut model = Sequential::new();
+        model.add(LayerEnum::dense(Some(1), 3));
+        model.add(LayerEnum::dense(None, 1));
+        model.add_constraint(0, LearnableParams::Weights, Constraint::UnitNorm { axis: 1 });
+        model.compile(MSE{}, SGD::new(0.01), vec![]).unwrap();
+
+        // pretrained weights which do not satisfy the constraint
+        let pretrained = Tensor::new(vec![2.0, -1.0, 0.5], vec![1, 3]);
+        *model.layers[0].get_param(&LearnableParams::Weights) = pretrained.clone();
+        model.set_trainable(0, false);
+        model.fit(&dataset, 3, false);
+        assert_eq!(model.layers[0].get_param(&LearnableParams::Weights), &pretrained);
+    }
+
+    #[test]
+    fn test_graph_constraints() {
+        let inputs = Tensor::random_normal(vec![20, 3], 0.0, 1.0, 1);
+        let targets = Tensor::random_normal(vec![20, 2], 0.0, 1.0, 2);
+
+        let mut model = Graph::new();
+        model.add_input("x", vec![3]);
+        model.add_layer("dense", LayerEnum::dense(None, 2), "x");
+        model.add_output("dense", MSE{}, 1.0, vec![]);
+        model.add_constraint("dense", LearnableParams::Weights, Constraint::UnitNorm { axis: 0 });
+        model.compile(SGD::new(0.01)).unwrap();
+        model.fit_tensors(&[inputs], &[targets], 5, false);
+        assert_close(&column_norms(model.layers[0].get_param(&LearnableParams::Weights)), &[1.0, 1.0]);
+
+        // a frozen node keeps its weights, also when the model is compiled again
+        model.set_trainable("dense", false);
+        model.compile(SGD::new(0.01)).unwrap();
+        let weights = model.layers[0].get_param(&LearnableParams::Weights).clone();
+        model.fit_tensors(&[Tensor::random_normal(vec![20, 3], 0.0, 1.0, 3)], &[Tensor::random_normal(vec![20, 2], 0.0, 1.0, 4)], 2, false);
+        assert_eq!(model.layers[0].get_param(&LearnableParams::Weights), &weights);
+        model.set_trainable("dense", true);
+
+        model.set_trainable("x", false);
+        assert_eq!(model.compile(SGD::new(0.01)), Err(GraphError::NotFreezable { node: "x".to_string() }));
+        model.set_trainable("x", true);
+
+        // only layers have parameters
+        model.add_constraint("x", LearnableParams::Weights, Constraint::NonNeg);
+        assert_eq!(model.compile(SGD::new(0.01)), Err(GraphError::UnknownParameter {
+            node: "x".to_string(),
+            param: LearnableParams::Weights,
+        }));
+    }
+}
@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod dataset_tests {
-    use newron::dataset::Dataset;
+    use newron::dataset::{Dataset, RowType, ColumnType};
     use std::path::Path;
     #[test]
     // This test asserts a good implementation of
@@ -27,6 +27,38 @@ mod dataset_tests {
         assert_eq!(format!("{:?}", dataset), result);
     }
 
+    #[test]
+    fn test_split_seed() {
+        let split = |seed: u32| {
+            let mut dataset = Dataset::from_raw_data((0..20).map(|i| vec![i as f64, 0.0]).collect()).unwrap();
+            dataset.set_seed(seed);
+            dataset.split_train_test(0.5, true);
+            assert_eq!(dataset.count_row_type(&RowType::Train), 10);
+            dataset.get_tensor(RowType::Train, ColumnType::Feature)
+        };
+
+        assert_eq!(split(1), split(1));
+        assert_ne!(split(1), split(2));
+    }
+
+    #[test]
+    fn test_batches() {
+        let mut dataset = Dataset::from_raw_data((0..10).map(|i| vec![i as f64, -(i as f64)]).collect()).unwrap();
+        for index in (0..10).step_by(3) {
+            dataset.set_row_type(RowType::Test, index);
+        }
+
+        let batches: Vec<_> = dataset.batches(RowType::Train, 4).collect();
+        let sizes: Vec<usize> = batches.iter().map(|(features, _)| features.shape[0]).collect();
+        assert_eq!(sizes, vec![4, 2]);
+
+        // the batches cover the rows of the type in order
+        let features: Vec<f64> = batches.iter().flat_map(|(features, _)| features.data.to_vec()).collect();
+        let targets: Vec<f64> = batches.iter().flat_map(|(_, targets)| targets.data.to_vec()).collect();
+        assert_eq!(features, vec![1.0, 2.0, 4.0, 5.0, 7.0, 8.0]);
+        assert_eq!(targets, vec![-1.0, -2.0, -4.0, -5.0, -7.0, -8.0]);
+    }
+
     #[test]
     fn test_load_csv() {
         let dataset = Dataset::from_csv(Path::new("datasets/winequality-white.csv"), true).unwrap();
@@ -0,0 +1,144 @@
+#[cfg(test)]
+mod graph_tests {
+    use newron::graph::{Graph, GraphError};
+    use newron::dataset::{Dataset, RowType};
+    use newron::layers::LayerEnum::{self, *};
+    use newron::layers::merge::{Merge, MergeOp};
+    use newron::loss::{mse::MSE, categorical_entropy::CategoricalEntropy};
+    use newron::metrics::{Average, Metric, MetricError};
+    use newron::optimizers::sgd::SGD;
+    use newron::tensor::Tensor;
+
+    #[test]
+    fn test_merge() {
+        let a = Tensor::new(vec![1.0, 2.0, 3.0, 4.0], vec![2, 2]);
+        let b = Tensor::new(vec![5.0, 6.0], vec![2, 1]);
+
+        let mut concat = Merge::new(MergeOp::Concatenate);
+        assert_eq!(concat.build(&[vec![2], vec![1]]), Ok(vec![3]));
+        assert!(concat.build(&[vec![2, 3], vec![1, 3]]).is_err());
+        let output = concat.forward(vec![a.clone(), b.clone()]);
+        assert_eq!(output, Tensor::new(vec![1.0, 2.0, 5.0, 3.0, 4.0, 6.0], vec![2, 3]));
+        let grads = concat.backward(&Tensor::new(vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6], vec![2, 3]));
+        assert_eq!(grads, vec![Tensor::new(vec![0.1, 0.2, 0.4, 0.5], vec![2, 2]), Tensor::new(vec![0.3, 0.6], vec![2, 1])]);
+
+        let mut multiply = Merge::new(MergeOp::Multiply);
+        assert!(multiply.build(&[vec![2], vec![1]]).is_err());
+        let c = Tensor::new(vec![2.0, 0.5, 1.0, -1.0], vec![2, 2]);
+        assert_eq!(multiply.forward(vec![a.clone(), c.clone()]), Tensor::new(vec![2.0, 1.0, 3.0, -4.0], vec![2, 2]));
+        let grads = multiply.backward(&Tensor::one(vec![2, 2]));
+        assert_eq!(grads, vec![c, a]);
+    }
+
+    #[test]
+    fn test_graph_errors() {
+        let mut model = Graph::new();
+        model.add_input("x", vec![3]);
+        model.add_layer("dense", LayerEnum::dense(None, 4), "y");
+        assert_eq!(model.compile(SGD::new(0.1)).err(),
+            Some(GraphError::UnknownNode {node: "dense".to_string(), input: "y".to_string()}));
+
+        let mut model = Graph::new();
+        model.add_input("x", vec![3]);
+        model.add_layer("x", ReLU, "x");
+        assert_eq!(model.compile(SGD::new(0.1)).err(), Some(GraphError::DuplicateNode {node: "x".to_string()}));
+
+        let mut model = Graph::new();
+        model.add_input("x", vec![3]);
+        model.add_layer("dense", LayerEnum::dense(None, 4), "x");
+        model.add_merge("sum", MergeOp::Add, &["x", "dense"]);
+        model.add_output("sum", MSE{}, 1.0, vec![]);
+        let error = model.compile(SGD::new(0.1)).err().unwrap();
+        assert_eq!(error, GraphError::IncompatibleShape {
+            node: "sum".to_string(),
+            node_type: "Add".to_string(),
+            input_shapes: vec![vec![3], vec![4]],
+            message: "all the inputs must have the same shape".to_string(),
+        });
+        assert_eq!(error.to_string(), "Node sum (Add) with input shapes [[3], [4]]: all the inputs must have the same shape.");
+
+        let mut model = Graph::new();
+        model.add_input("x", vec![3]);
+        assert_eq!(model.compile(SGD::new(0.1)).err(), Some(GraphError::MissingInputOrOutput));
+    }
+
+    #[test]
+    fn test_residual_fit() {
+        // y = x1 > x2, with a skip connection around the hidden layers
+        let mut data = Vec::new();
+        for i in 0..60 {
+            let (x1, x2) = ((i % 7) as f64 / 7.0, (i % 5) as f64 / 5.0);
+            data.push(vec![x1, x2, (x1 > x2) as usize as f64]);
+        }
+        let mut dataset = Dataset::from_raw_data(data).unwrap();
+        dataset.one_hot_encode(2);
+
+        let mut model = Graph::new();
+        model.add_input("x", vec![2]);
+        model.add_layer("hidden", LayerEnum::dense(None, 2), "x");
+        model.add_layer("relu", ReLU, "hidden");
+        model.add_merge("residual", MergeOp::Add, &["x", "relu"]);
+        model.add_layer("logits", LayerEnum::dense(None, 2), "residual");
+        model.add_output("logits", CategoricalEntropy{}, 1.0, vec![Metric::Accuracy]);
+        model.set_shuffle(true);
+        model.compile(SGD::new(0.1)).unwrap();
+        assert_eq!(model.layers.len(), 3);
+
+        let loss_before = model.evaluate(&dataset, RowType::Train).unwrap().loss;
+        model.fit(&dataset, 30, false);
+        let evaluation = model.evaluate(&dataset, RowType::Train).unwrap();
+        assert!(evaluation.loss < loss_before);
+        assert_eq!(evaluation.get("logits loss"), Some(evaluation.loss));
+        assert!(evaluation.get("logits Accuracy").unwrap() > 0.5);
+    }
+
+    #[test]
+    fn test_graph_unknown_class() {
+        let mut model = Graph::new();
+        model.add_input("x", vec![2]);
+        model.add_layer("logits", LayerEnum::dense(None, 3), "x");
+        model.add_output("logits", CategoricalEntropy{}, 1.0, vec![Metric::Recall(Average::Class(5))]);
+        model.compile(SGD::new(0.1)).unwrap();
+
+        let inputs = Tensor::random_normal(vec![4, 2], 0.0, 1.0, 1);
+        let targets = Tensor::new(vec![1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0], vec![4, 3]);
+        assert_eq!(model.evaluate_tensors(&[inputs], &[targets]).err(), Some(MetricError::UnknownClass { class: 5, classes: 3 }));
+    }
+
+    #[test]
+    fn test_multiple_inputs_outputs() {
+        // two inputs, a shared trunk and two heads: the sum and the product
+        let a = Tensor::random_normal(vec![50, 2], 0.0, 0.5, 1);
+        let b = Tensor::random_normal(vec![50, 3], 0.0, 0.5, 2);
+        let sums = Tensor::new(a.data.chunks(2).zip(b.data.chunks(3))
+            .map(|(x, y)| x.iter().chain(y).sum()).collect(), vec![50, 1]);
+        let products = Tensor::new(a.data.chunks(2).zip(b.data.chunks(3))
+            .map(|(x, y)| x[0] * y[0]).collect(), vec![50, 1]);
+
+        let mut model = Graph::new();
+        model.add_input("a", vec![2]);
+        model.add_input("b", vec![3]);
+        model.add_merge("concat", MergeOp::Concatenate, &["a", "b"]);
+        model.add_layer("trunk", LayerEnum::dense(None, 8), "concat");
+        model.add_layer("activation", TanH, "trunk");
+        model.add_layer("sum", LayerEnum::dense(None, 1), "activation");
+        model.add_layer("product", LayerEnum::dense(None, 1), "activation");
+        model.add_output("sum", MSE{}, 1.0, vec![]);
+        model.add_output("product", MSE{}, 0.5, vec![]);
+        model.compile(SGD::new(0.002)).unwrap();
+
+        let inputs = vec![a, b];
+        let targets = vec![sums, products];
+        let before = model.evaluate_tensors(&inputs, &targets).unwrap();
+        assert!((before.loss - before.metrics[0].value - 0.5 * before.metrics[1].value).abs() < 1e-12);
+
+        model.fit_tensors(&inputs, &targets, 50, false);
+        let after = model.evaluate_tensors(&inputs, &targets).unwrap();
+        assert!(after.loss < before.loss);
+        assert!(after.get("sum loss").unwrap() < before.get("sum loss").unwrap());
+
+        let predictions = model.predict_tensors(inputs);
+        assert_eq!(predictions.len(), 2);
+        assert_eq!(predictions[1].shape, vec![50, 1]);
+    }
+}
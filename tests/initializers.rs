@@ -0,0 +1,131 @@
+#[cfg(test)]
+mod initializers_tests {
+    use newron::initializers::Initializer;
+    use newron::layers::LayerEnum::*;
+    use newron::layers::conv1d::Conv1D;
+    use newron::layers::conv2d::Conv2D;
+    use newron::layers::embedding::Embedding;
+    use newron::layers::recurrent::{Recurrent, CellType};
+    use newron::layers::layer::Layer;
+    use newron::layers::layer::LearnableParams;
+    use newron::loss::mse::MSE;
+    use newron::optimizers::sgd::SGD;
+    use newron::sequential::Sequential;
+    use newron::tensor::Tensor;
+
+    fn mean_and_stdev(tensor: &Tensor) -> (f64, f64) {
+        let count = tensor.data.len() as f64;
+        let mean = tensor.data.iter().sum::<f64>() / count;
+        let variance = tensor.data.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / count;
+        (mean, variance.sqrt())
+    }
+
+    #[test]
+    fn test_random_initializers() {
+        // (initializer, limit of uniform distributions or stdev of normal ones)
+        let cases = [
+            (Initializer::GlorotUniform, (6.0_f64 / 300.0).sqrt()),
+            (Initializer::HeUniform, (6.0_f64 / 100.0).sqrt()),
+            (Initializer::LeCunUniform, (3.0_f64 / 100.0).sqrt()),
+        ];
+        for (initializer, limit) in cases {
+            let tensor = initializer.initialize(vec![100, 200], 100, 200, 1);
+            assert_eq!(tensor.shape, vec![100, 200]);
+            assert!(tensor.data.iter().all(|x| x.abs() <= limit));
+            let (mean, stdev) = mean_and_stdev(&tensor);
+            assert!(mean.abs() < 0.01 * limit);
+            // stdev of a uniform distribution
+            assert!((stdev - limit / 3.0_f64.sqrt()).abs() < 0.02 * limit, "{:?}", initializer);
+        }
+
+        let cases = [
+            (Initializer::GlorotNormal, (2.0_f64 / 300.0).sqrt()),
+            (Initializer::HeNormal, (2.0_f64 / 100.0).sqrt()),
+            (Initializer::LeCunNormal, (1.0_f64 / 100.0).sqrt()),
+        ];
+        for (initializer, expected) in cases {
+            let (mean, stdev) = mean_and_stdev(&initializer.initialize(vec![100, 200], 100, 200, 2));
+            assert!(mean.abs() < 0.01 * expected);
+            assert!((stdev - expected).abs() < 0.02 * expected, "{:?}", initializer);
+        }
+
+        let tensor = Initializer::TruncatedNormal {mean: 1.0, stdev: 0.5}.initialize(vec![10000], 1, 1, 3);
+        assert!(tensor.data.iter().all(|x| (x - 1.0).abs() <= 1.0));
+        assert!((mean_and_stdev(&tensor).0 - 1.0).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_constant_initializers() {
+        assert_eq!(Initializer::Zeros.initialize(vec![2, 3], 2, 3, 0), Tensor::zero(vec![2, 3]));
+        assert_eq!(Initializer::Ones.initialize(vec![2, 3], 2, 3, 0), Tensor::one(vec![2, 3]));
+        assert_eq!(Initializer::Constant(0.5).initialize(vec![2], 1, 2, 0), Tensor::new(vec![0.5, 0.5], vec![2]));
+    }
+
+    #[test]
+    fn test_orthogonal() {
+        for shape in [vec![6, 4], vec![4, 6]] {
+            let tensor = Initializer::Orthogonal {gain: 2.0}.initialize(shape.to_vec(), shape[0], shape[1], 4);
+            // the smaller dimension is made of orthogonal vectors of norm 2
+            let product = if shape[0] >= shape[1] {
+                &tensor.get_transpose() * &tensor
+            } else {
+                &tensor * &tensor.get_transpose()
+            };
+            let size = product.shape[0];
+            for i in 0..size {
+                for j in 0..size {
+                    let expected = if i == j { 4.0 } else { 0.0 };
+                    assert!((product.data[i * size + j] - expected).abs() < 1e-12);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_dense_initializers() {
+        let mut model = Sequential::new();
+        model.add(Dense {
+            input_units: Some(3),
+            output_units: 2,
+            kernel_initializer: Initializer::Constant(0.1),
+            bias_initializer: Initializer::Ones,
+            kernel_regularizer: None,
+            bias_regularizer: None,
+        });
+        model.compile(MSE{}, SGD::new(0.1), vec![]).unwrap();
+
+        assert_eq!(model.layers[0].get_param(&LearnableParams::Weights), &Tensor::new(vec![0.1; 6], vec![3, 2]));
+        assert_eq!(model.layers[0].get_param(&LearnableParams::Biases), &Tensor::one(vec![1, 2]));
+        assert_eq!(model.layers[0].get_info().trainable_param, 8);
+    }
+
+    #[test]
+    fn test_layer_initializers() {
+        // default kernels: Glorot uniform (as Dense) with the receptive field in the fans
+        let mut conv = Conv2D::new(4, 8, (3, 3), 1);
+        let (_, stdev) = mean_and_stdev(conv.get_param(&LearnableParams::Weights));
+        assert!((stdev - (2.0_f64 / 108.0).sqrt()).abs() < 0.1 * stdev);
+
+        let mut conv = Conv2D::new(4, 8, (3, 3), 1).with_kernel_initializer(Initializer::Constant(0.5));
+        assert_eq!(conv.get_param(&LearnableParams::Weights), &Tensor::new(vec![0.5; 288], vec![8, 4, 3, 3]));
+        let mut conv = Conv1D::new(2, 3, 2, 1).with_kernel_initializer(Initializer::Ones);
+        assert_eq!(conv.get_param(&LearnableParams::Weights), &Tensor::one(vec![3, 2, 2, 1]));
+
+        let mut embedding = Embedding::new(10, 4, 1).with_embeddings_initializer(Initializer::Zeros);
+        assert_eq!(embedding.get_param(&LearnableParams::Weights), &Tensor::zero(vec![10, 4]));
+
+        let mut lstm = Recurrent::new(CellType::LSTM, 3, 4, 1)
+            .with_kernel_initializer(Initializer::Constant(0.1))
+            .with_recurrent_initializer(Initializer::Orthogonal { gain: 1.0 });
+        assert_eq!(lstm.get_param(&LearnableParams::Weights), &Tensor::new(vec![0.1; 48], vec![3, 16]));
+        // the rows of the [4, 16] recurrent kernel are orthonormal
+        let recurrent = lstm.get_param(&LearnableParams::RecurrentWeights).clone();
+        let product = &recurrent * &recurrent.get_transpose();
+        for i in 0..4 {
+            for j in 0..4 {
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert!((product.data[i * 4 + j] - expected).abs() < 1e-12);
+            }
+        }
+    }
+}
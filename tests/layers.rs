@@ -0,0 +1,552 @@
+#[cfg(test)]
+mod layers_tests {
+    use newron::layers::layer::Layer;
+    use newron::layers::conv2d::Conv2D;
+    use newron::layers::pooling2d::{Pool2D, GlobalPool2D, PoolMode};
+    use newron::layers::conv1d::Conv1D;
+    use newron::layers::pooling1d::Pool1D;
+    use newron::layers::dropout::Dropout;
+    use newron::layers::flatten::Flatten;
+    use newron::layers::reshape::Reshape;
+    use newron::layers::batch_norm::BatchNorm;
+    use newron::layers::layer_norm::{LayerNorm, NormMode};
+    use newron::layers::embedding::Embedding;
+    use newron::layers::recurrent::{Recurrent, CellType};
+    use newron::layers::bidirectional::{Bidirectional, MergeMode};
+    use newron::layers::attention::MultiHeadAttention;
+    use newron::layers::transformer::TransformerEncoderBlock;
+    use newron::layers::activation::{Activation, ActivationFn};
+    use newron::layers::prelu::PReLU;
+    use newron::layers::{DataFormat, Padding};
+    use newron::layers::layer::LearnableParams;
+    use newron::tensor::Tensor;
+
+    // Compare the gradients computed by `layer.backward` with finite differences
+    // of the loss sum(output * coefficients) where coefficients are random
+    fn check_gradients(layer: &mut dyn Layer, input: Tensor) {
+        let epsilon = 1e-6;
+        let tolerance = 1e-5;
+
+        let output = layer.forward(input.clone(), true);
+        let coefficients = Tensor::random_normal(output.shape.to_vec(), 0.0, 1.0, 7);
+        let input_grad = layer.backward(&coefficients);
+        assert_eq!(input_grad.shape, input.shape);
+
+        let loss = |layer: &mut dyn Layer, x: Tensor| -> f64 {
+            layer.forward(x, true).mult_el(&coefficients).data.iter().sum()
+        };
+
+        for i in 0..input.data.len() {
+            let mut plus = input.clone();
+            plus.data[i] += epsilon;
+            let mut minus = input.clone();
+            minus.data[i] -= epsilon;
+            let numerical = (loss(layer, plus) - loss(layer, minus)) / (2.0 * epsilon);
+            assert!((numerical - input_grad.data[i]).abs() < tolerance,
+                "input gradient {}: analytic {} != numerical {}", i, input_grad.data[i], numerical);
+        }
+
+        for param in layer.get_params_list() {
+            let grad = layer.get_grad(&param).clone();
+            for i in 0..grad.data.len() {
+                layer.get_param(&param).data[i] += epsilon;
+                let loss_plus = loss(layer, input.clone());
+                layer.get_param(&param).data[i] -= 2.0 * epsilon;
+                let loss_minus = loss(layer, input.clone());
+                layer.get_param(&param).data[i] += epsilon;
+
+                let numerical = (loss_plus - loss_minus) / (2.0 * epsilon);
+                assert!((numerical - grad.data[i]).abs() < tolerance,
+                    "parameter gradient {}: analytic {} != numerical {}", i, grad.data[i], numerical);
+            }
+        }
+    }
+
+    #[test]
+    fn test_conv2d_forward() {
+        let mut conv = Conv2D::new(1, 1, (2, 2), 0);
+        *conv.get_param(&LearnableParams::Weights) = Tensor::new(vec![1.0, 0.0, 0.0, 1.0], vec![1, 1, 2, 2]);
+        *conv.get_param(&LearnableParams::Biases) = Tensor::new(vec![0.5], vec![1, 1]);
+
+        let input = Tensor::new(vec![1.0, 2.0, 3.0,
+                                     4.0, 5.0, 6.0,
+                                     7.0, 8.0, 9.0], vec![1, 1, 3, 3]);
+
+        let result = Tensor::new(vec![6.5, 8.5,
+                                      12.5, 14.5], vec![1, 1, 2, 2]);
+
+        assert_eq!(conv.forward(input, false), result);
+    }
+
+    #[test]
+    fn test_conv2d_output_shape() {
+        let conv = Conv2D::new(3, 8, (3, 3), 0)
+            .with_stride((2, 2))
+            .with_padding(Padding::Same);
+        assert_eq!(conv.compute_output_shape(&[3, 5, 5]), vec![8, 3, 3]);
+
+        let conv = Conv2D::new(3, 8, (3, 3), 0)
+            .with_dilation((2, 2))
+            .with_data_format(DataFormat::ChannelsLast);
+        assert_eq!(conv.compute_output_shape(&[5, 6, 3]), vec![1, 2, 8]);
+
+        let mut conv = Conv2D::new(2, 4, (3, 3), 0).with_padding(Padding::Explicit(1, 0));
+        let output = conv.forward(Tensor::zero(vec![2, 2, 4, 4]), false);
+        assert_eq!(output.shape, vec![2, 4, 4, 2]);
+        assert_eq!(conv.build(&[2, 4, 4]), Ok(vec![4, 4, 2]));
+        assert_eq!(conv.get_info().output_shape, vec![4, 4, 2]);
+
+        assert!(conv.build(&[3, 4, 4]).is_err());
+        assert!(conv.build(&[2, 4, 2]).is_err());
+    }
+
+    #[test]
+    fn test_conv2d_gradients() {
+        let input = Tensor::random_normal(vec![2, 2, 5, 4], 0.0, 1.0, 1);
+        let mut conv = Conv2D::new(2, 3, (3, 2), 2)
+            .with_stride((2, 1))
+            .with_padding(Padding::Same)
+            .with_dilation((1, 2));
+        check_gradients(&mut conv, input);
+
+        let input = Tensor::random_normal(vec![2, 5, 4, 2], 0.0, 1.0, 3);
+        let mut conv = Conv2D::new(2, 3, (2, 2), 4).with_data_format(DataFormat::ChannelsLast);
+        check_gradients(&mut conv, input);
+    }
+
+    #[test]
+    fn test_max_pool2d() {
+        let input = Tensor::new(vec![1.0, 2.0, 5.0, 0.0,
+                                     3.0, 4.0, 1.0, 1.0,
+                                     0.0, 0.0, 2.0, 2.0,
+                                     9.0, 0.0, 2.0, 3.0], vec![1, 1, 4, 4]);
+
+        let mut pool = Pool2D::new(PoolMode::Max, (2, 2));
+        let output = pool.forward(input, false);
+        assert_eq!(output, Tensor::new(vec![4.0, 5.0, 9.0, 3.0], vec![1, 1, 2, 2]));
+
+        // gradient is routed to the maximum of each window
+        let input_grad = pool.backward(&Tensor::new(vec![1.0, 2.0, 3.0, 4.0], vec![1, 1, 2, 2]));
+        assert_eq!(input_grad.data, vec![0.0, 0.0, 2.0, 0.0,
+                                         0.0, 1.0, 0.0, 0.0,
+                                         0.0, 0.0, 0.0, 0.0,
+                                         3.0, 0.0, 0.0, 4.0]);
+    }
+
+    #[test]
+    fn test_avg_pool2d_same_padding() {
+        let input = Tensor::new(vec![1.0, 2.0, 3.0,
+                                     4.0, 5.0, 6.0,
+                                     7.0, 8.0, 9.0], vec![1, 3, 3, 1]);
+
+        let mut pool = Pool2D::new(PoolMode::Average, (2, 2))
+            .with_padding(Padding::Same)
+            .with_data_format(DataFormat::ChannelsLast);
+        assert_eq!(pool.compute_output_shape(&[3, 3, 1]), vec![2, 2, 1]);
+
+        // padded elements are not counted in the average
+        let output = pool.forward(input, false);
+        assert_eq!(output, Tensor::new(vec![3.0, 4.5, 7.5, 9.0], vec![1, 2, 2, 1]));
+    }
+
+    #[test]
+    fn test_pool2d_gradients() {
+        let input = Tensor::random_normal(vec![2, 2, 5, 5], 0.0, 1.0, 5);
+        check_gradients(&mut Pool2D::new(PoolMode::Max, (2, 2)).with_stride((1, 2)), input.clone());
+        check_gradients(&mut Pool2D::new(PoolMode::Average, (3, 2)).with_padding(Padding::Same), input.clone());
+        check_gradients(&mut GlobalPool2D::new(PoolMode::Max, DataFormat::ChannelsFirst), input.clone());
+        check_gradients(&mut GlobalPool2D::new(PoolMode::Average, DataFormat::ChannelsLast), input);
+    }
+
+    #[test]
+    fn test_global_pooling2d() {
+        // [1, 2, 2, 2] channels last: channel 0 = 1, 3, 5, 7 and channel 1 = 2, 4, 6, 8
+        let input = Tensor::new(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0], vec![1, 2, 2, 2]);
+
+        let mut pool = GlobalPool2D::new(PoolMode::Average, DataFormat::ChannelsLast);
+        assert_eq!(pool.forward(input.clone(), false), Tensor::new(vec![4.0, 5.0], vec![1, 2]));
+        assert_eq!(pool.build(&[2, 2, 2]), Ok(vec![2]));
+        assert_eq!(pool.get_info().output_shape, vec![2]);
+
+        let mut pool = GlobalPool2D::new(PoolMode::Max, DataFormat::ChannelsFirst);
+        assert_eq!(pool.forward(input, false), Tensor::new(vec![4.0, 8.0], vec![1, 2]));
+    }
+
+    #[test]
+    fn test_conv1d_causal() {
+        let mut conv = Conv1D::new(1, 1, 2, 0)
+            .with_padding(Padding::Causal)
+            .with_dilation(2);
+        *conv.get_param(&LearnableParams::Weights) = Tensor::new(vec![1.0, 10.0], vec![1, 1, 2, 1]);
+
+        // y[t] = x[t - 2] + 10 * x[t], the first inputs only see zero padding
+        let input = Tensor::new(vec![1.0, 2.0, 3.0, 4.0], vec![1, 4, 1]);
+        let output = conv.forward(input, false);
+        assert_eq!(output, Tensor::new(vec![10.0, 20.0, 31.0, 42.0], vec![1, 4, 1]));
+        assert_eq!(conv.build(&[4, 1]), Ok(vec![4, 1]));
+        assert_eq!(conv.get_info().output_shape, vec![4, 1]);
+
+        // flat inputs are interpreted as [batch, time, channels]
+        let mut conv = Conv1D::new(2, 3, 3, 0).with_stride(2);
+        assert_eq!(conv.compute_output_shape(&[7, 2]), vec![3, 3]);
+        assert_eq!(conv.forward(Tensor::zero(vec![5, 14]), false).shape, vec![5, 3, 3]);
+    }
+
+    #[test]
+    fn test_conv1d_gradients() {
+        let input = Tensor::random_normal(vec![2, 6, 2], 0.0, 1.0, 1);
+        let mut conv = Conv1D::new(2, 3, 3, 2)
+            .with_padding(Padding::Causal)
+            .with_dilation(2);
+        check_gradients(&mut conv, input);
+
+        let input = Tensor::random_normal(vec![2, 12], 0.0, 1.0, 3);
+        let mut conv = Conv1D::new(3, 2, 2, 4).with_stride(2).with_padding(Padding::Same);
+        check_gradients(&mut conv, input);
+    }
+
+    #[test]
+    fn test_pool1d() {
+        // [1, 4, 2]: channel 0 = 1, 3, 5, 2 and channel 1 = 8, 6, 4, 7
+        let input = Tensor::new(vec![1.0, 8.0, 3.0, 6.0, 5.0, 4.0, 2.0, 7.0], vec![1, 4, 2]);
+
+        let mut pool = Pool1D::new(PoolMode::Max, 2);
+        assert_eq!(pool.forward(input.clone(), false), Tensor::new(vec![3.0, 8.0, 5.0, 7.0], vec![1, 2, 2]));
+
+        let mut pool = Pool1D::new(PoolMode::Average, 2).with_stride(1);
+        assert_eq!(pool.compute_output_shape(&[4, 2]), vec![3, 2]);
+        assert_eq!(pool.forward(input, false), Tensor::new(vec![2.0, 7.0, 4.0, 5.0, 3.5, 5.5], vec![1, 3, 2]));
+
+        let input = Tensor::random_normal(vec![2, 5, 3], 0.0, 1.0, 5);
+        check_gradients(&mut Pool1D::new(PoolMode::Max, 2), input.clone());
+        check_gradients(&mut Pool1D::new(PoolMode::Average, 3).with_stride(2).with_padding(Padding::Same), input);
+    }
+
+    #[test]
+    fn test_dropout_masks() {
+        let input = Tensor::one(vec![4, 8]);
+
+        // any seed, without overflow when drawing the masks
+        let mut dropout = Dropout::new(0.5, u32::MAX);
+        let first = dropout.forward(input.clone(), true);
+        let second = dropout.forward(input.clone(), true);
+        assert_ne!(first, second);
+        assert!(first.data.iter().all(|x| *x == 0.0 || *x == 2.0));
+        assert_eq!(dropout.forward(input.clone(), false), input);
+
+        // the masks only depend on the seed
+        let mut other = Dropout::new(0.5, u32::MAX);
+        assert_eq!(other.forward(input.clone(), true), first);
+        assert_eq!(other.forward(input, true), second);
+    }
+
+    #[test]
+    fn test_flatten_reshape() {
+        let input = Tensor::random_normal(vec![2, 3, 2, 2], 0.0, 1.0, 6);
+
+        let mut flatten = Flatten::new();
+        assert_eq!(flatten.build(&[3, 2, 2]), Ok(vec![12]));
+        let output = flatten.forward(input.clone(), true);
+        assert_eq!(output.shape, vec![2, 12]);
+        assert_eq!(output.data, input.data);
+        assert_eq!(flatten.backward(&output), input);
+
+        let mut reshape = Reshape::new(vec![4, 3]);
+        assert_eq!(reshape.build(&[3, 2, 2]), Ok(vec![4, 3]));
+        assert!(reshape.build(&[3, 2, 3]).is_err());
+        let output = reshape.forward(input.clone(), true);
+        assert_eq!(output.shape, vec![2, 4, 3]);
+        assert_eq!(reshape.backward(&output), input);
+    }
+
+    #[test]
+    fn test_batch_norm() {
+        let input = Tensor::new(vec![1.0, 10.0,
+                                     3.0, 20.0,
+                                     5.0, 30.0], vec![3, 2]);
+
+        let mut norm = BatchNorm::new(0.0, 0.0, DataFormat::ChannelsFirst);
+        assert_eq!(norm.build(&[2]), Ok(vec![2]));
+        let info = norm.get_info();
+        assert_eq!((info.trainable_param, info.non_trainable_param), (4, 4));
+
+        // each feature is normalized over the batch
+        let output = norm.forward(input.clone(), true);
+        let scale = (1.5f64).sqrt();
+        let expected = vec![-scale, -scale, 0.0, 0.0, scale, scale];
+        for (value, expected) in output.data.iter().zip(expected) {
+            assert!((value - expected).abs() < 1e-12);
+        }
+
+        // with a momentum of 0 the moving statistics are the last batch statistics
+        assert_eq!(norm.get_param(&LearnableParams::MovingMean).data, vec![3.0, 20.0]);
+        assert_eq!(norm.get_param(&LearnableParams::MovingVariance).data, vec![8.0 / 3.0, 200.0 / 3.0]);
+        assert_eq!(norm.forward(input.clone(), false), output);
+
+        // a frozen layer uses and keeps its moving statistics during training
+        norm.set_trainable(false);
+        let shifted = &input + &Tensor::new(vec![1.0, 1.0], vec![1, 2]);
+        assert_eq!(norm.forward(shifted.clone(), true), norm.forward(shifted, false));
+        assert_eq!(norm.get_param(&LearnableParams::MovingMean).data, vec![3.0, 20.0]);
+    }
+
+    #[test]
+    fn test_batch_norm_gradients() {
+        let input = Tensor::random_normal(vec![4, 3], 1.0, 2.0, 8);
+        let mut norm = BatchNorm::new(0.9, 1e-3, DataFormat::ChannelsFirst);
+        norm.build(&[3]).unwrap();
+        *norm.get_param(&LearnableParams::Gamma) = Tensor::random_normal(vec![1, 3], 1.0, 0.5, 9);
+        check_gradients(&mut norm, input);
+
+        // normalization of each channel over the batch and the spatial positions
+        let input = Tensor::random_normal(vec![2, 3, 2, 2], 0.0, 1.0, 10);
+        check_gradients(&mut BatchNorm::new(0.9, 1e-3, DataFormat::ChannelsFirst), input.clone());
+        check_gradients(&mut BatchNorm::new(0.9, 1e-3, DataFormat::ChannelsLast), input);
+    }
+
+    #[test]
+    fn test_layer_norm() {
+        let input = Tensor::new(vec![1.0, 2.0, 3.0, 4.0,
+                                     2.0, 2.0, 2.0, 2.0], vec![2, 4]);
+
+        // each sample is normalized over its features
+        let mut norm = LayerNorm::new(NormMode::Layer, 0.0);
+        assert_eq!(norm.build(&[4]), Ok(vec![4]));
+        assert_eq!(norm.get_info().trainable_param, 8);
+        *norm.get_param(&LearnableParams::Beta) = Tensor::new(vec![1.0; 4], vec![1, 4]);
+        let output = norm.forward(input.clone(), true);
+        let scale = (1.25f64).sqrt();
+        let expected = vec![1.0 - 1.5 / scale, 1.0 - 0.5 / scale, 1.0 + 0.5 / scale, 1.0 + 1.5 / scale];
+        for (value, expected) in output.data[..4].iter().zip(expected) {
+            assert!((value - expected).abs() < 1e-12);
+        }
+
+        let mut norm = LayerNorm::new(NormMode::RMS, 0.0);
+        let output = norm.forward(input, true);
+        assert_eq!(output.data[4..], [1.0; 4]);
+        assert_eq!(norm.get_info().layer_type, "RMSNorm");
+    }
+
+    #[test]
+    fn test_layer_norm_gradients() {
+        let input = Tensor::random_normal(vec![3, 5], 0.5, 2.0, 11);
+        for mode in [NormMode::Layer, NormMode::RMS] {
+            let mut norm = LayerNorm::new(mode, 1e-3);
+            norm.build(&[5]).unwrap();
+            *norm.get_param(&LearnableParams::Gamma) = Tensor::random_normal(vec![1, 5], 1.0, 0.5, 12);
+            check_gradients(&mut norm, input.clone());
+        }
+
+        // sequences of shape [batch, time, features]
+        let input = Tensor::random_normal(vec![2, 3, 4], 0.0, 1.0, 13);
+        check_gradients(&mut LayerNorm::new(NormMode::Layer, 1e-5), input.clone());
+        check_gradients(&mut LayerNorm::new(NormMode::RMS, 1e-5), input);
+    }
+
+    #[test]
+    fn test_embedding() {
+        let mut embedding = Embedding::new(5, 2, 0);
+        *embedding.get_param(&LearnableParams::Weights) = Tensor::new((0..10).map(|x| x as f64).collect(), vec![5, 2]);
+        assert_eq!(embedding.build(&[3]), Ok(vec![3, 2]));
+
+        let input = Tensor::new(vec![4.0, 1.0, 4.0,
+                                     0.0, 1.0, 1.0], vec![2, 3]);
+        let output = embedding.forward(input, true);
+        assert_eq!(output, Tensor::new(vec![8.0, 9.0, 2.0, 3.0, 8.0, 9.0,
+                                            0.0, 1.0, 2.0, 3.0, 2.0, 3.0], vec![2, 3, 2]));
+
+        // only the rows 0, 1 and 4 have a gradient
+        let gradient = Tensor::new((0..12).map(|x| x as f64).collect(), vec![2, 3, 2]);
+        assert_eq!(embedding.backward(&gradient), Tensor::zero(vec![2, 3]));
+        assert_eq!(embedding.get_grad_rows(&LearnableParams::Weights), Some(vec![0, 1, 4]));
+        assert_eq!(embedding.get_grad(&LearnableParams::Weights),
+            &Tensor::new(vec![6.0, 7.0, 2.0 + 8.0 + 10.0, 3.0 + 9.0 + 11.0, 4.0, 6.0], vec![3, 2]));
+    }
+
+    #[test]
+    fn test_simple_rnn_forward() {
+        let mut rnn = Recurrent::new(CellType::SimpleRNN, 1, 1, 0).with_return_sequences(true);
+        *rnn.get_param(&LearnableParams::Weights) = Tensor::new(vec![0.5], vec![1, 1]);
+        *rnn.get_param(&LearnableParams::RecurrentWeights) = Tensor::new(vec![2.0], vec![1, 1]);
+        assert_eq!(rnn.build(&[2, 1]), Ok(vec![2, 1]));
+
+        // h1 = tanh(0.5 * 1), h2 = tanh(0.5 * 2 + 2 * h1)
+        let output = rnn.forward(Tensor::new(vec![1.0, 2.0], vec![1, 2, 1]), false);
+        let h1 = (0.5f64).tanh();
+        assert_eq!(output, Tensor::new(vec![h1, (1.0 + 2.0 * h1).tanh()], vec![1, 2, 1]));
+
+        // the initial state replaces the zero state
+        let mut rnn = rnn.with_initial_state(vec![Tensor::new(vec![1.0], vec![1, 1])]);
+        let output = rnn.forward(Tensor::new(vec![1.0, 2.0], vec![1, 2, 1]), false);
+        assert_eq!(output.data[0], (0.5f64 + 2.0).tanh());
+    }
+
+    #[test]
+    fn test_recurrent_gradients() {
+        let input = Tensor::random_normal(vec![2, 4, 3], 0.0, 1.0, 14);
+        for cell in [CellType::SimpleRNN, CellType::LSTM, CellType::GRU] {
+            let mut layer = Recurrent::new(cell, 3, 2, 15);
+            assert_eq!(layer.build(&[4, 3]), Ok(vec![2]));
+            assert_eq!(layer.get_params_list().len(), 3);
+            check_gradients(&mut layer, input.clone());
+
+            let initial_state = vec![Tensor::random_normal(vec![2, 2], 0.0, 1.0, 16); if cell == CellType::LSTM { 2 } else { 1 }];
+            let mut layer = Recurrent::new(cell, 3, 2, 17)
+                .with_return_sequences(true)
+                .with_go_backwards(true)
+                .with_initial_state(initial_state);
+            check_gradients(&mut layer, input.clone());
+        }
+    }
+
+    #[test]
+    fn test_bidirectional() {
+        let input = Tensor::random_normal(vec![2, 3, 2], 0.0, 1.0, 18);
+
+        let mut layer = Bidirectional::new(
+            Recurrent::new(CellType::GRU, 2, 3, 19).with_return_sequences(true),
+            Recurrent::new(CellType::GRU, 2, 3, 20).with_return_sequences(true),
+            MergeMode::Concat);
+        assert_eq!(layer.build(&[3, 2]), Ok(vec![3, 6]));
+        assert_eq!(layer.get_params_list().len(), 6);
+        assert_eq!(layer.get_params_list()[3], LearnableParams::Sublayer(1, Box::new(LearnableParams::Weights)));
+
+        // the backward direction sees the reversed sequence
+        let mut backward = Recurrent::new(CellType::GRU, 2, 3, 20).with_return_sequences(true).with_go_backwards(true);
+        let output = layer.forward(input.clone(), false);
+        let backward_output = backward.forward(input.clone(), false);
+        assert_eq!(output.data[3..6], backward_output.data[6..9]);
+        check_gradients(&mut layer, input.clone());
+
+        let mut layer = Bidirectional::new(
+            Recurrent::new(CellType::LSTM, 2, 3, 21),
+            Recurrent::new(CellType::LSTM, 2, 3, 22),
+            MergeMode::Sum);
+        assert_eq!(layer.build(&[3, 2]), Ok(vec![3]));
+        check_gradients(&mut layer, input);
+    }
+
+    #[test]
+    fn test_attention_masks() {
+        let input = Tensor::random_normal(vec![2, 3, 4], 0.0, 1.0, 23);
+
+        let mut layer = MultiHeadAttention::new(4, 2, 24).with_causal_mask(true);
+        assert_eq!(layer.build(&[3, 4]), Ok(vec![3, 4]));
+        assert!(layer.build(&[3, 5]).is_err());
+        assert_eq!(layer.get_params_list().len(), 8);
+        let output = layer.forward(input.clone(), false);
+
+        // each query attends to the current and previous positions only
+        let attention = layer.get_attention();
+        assert_eq!(attention.shape, vec![2, 2, 3, 3]);
+        for row in attention.data.chunks(9).flat_map(|matrix| matrix.chunks(3)) {
+            assert!((row.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+        }
+        assert_eq!(attention.data[1], 0.0);
+        assert_eq!(attention.data[5], 0.0);
+
+        // changing the last time step does not change the previous outputs
+        let mut future = input.clone();
+        future.data[8..12].copy_from_slice(&[1.0, 2.0, 3.0, 4.0]);
+        let future_output = layer.forward(future, false);
+        assert_eq!(output.data[..8], future_output.data[..8]);
+        check_gradients(&mut layer, input.clone());
+
+        // padded keys are ignored
+        let mut layer = MultiHeadAttention::new(4, 1, 25);
+        layer.build(&[3, 4]).unwrap();
+        layer.set_padding_mask(Some(Tensor::new(vec![1.0, 1.0, 0.0, 1.0, 0.0, 0.0], vec![2, 3])));
+        layer.forward(input.clone(), false);
+        let attention = layer.get_attention();
+        for i in 0..3 {
+            assert_eq!(attention.data[i * 3 + 2], 0.0);
+            assert_eq!(attention.data[9 + i * 3], 1.0);
+        }
+        check_gradients(&mut layer, input.clone());
+
+        // zero input vectors are treated as padding (second step of the first sample)
+        let mut padded = input;
+        padded.data[4..8].fill(0.0);
+        let mut layer = MultiHeadAttention::new(4, 2, 26).with_padding_mask(true);
+        layer.build(&[3, 4]).unwrap();
+        layer.forward(padded, false);
+        let attention = layer.get_attention();
+        assert!(attention.data[..18].chunks(3).all(|row| row[1] == 0.0));
+        assert!(attention.data[18..].chunks(3).all(|row| row[1] > 0.0));
+    }
+
+    #[test]
+    fn test_transformer_encoder_block() {
+        let input = Tensor::random_normal(vec![2, 3, 4], 0.0, 1.0, 27);
+
+        let mut layer = TransformerEncoderBlock::new(4, 2, 6, 28);
+        assert_eq!(layer.build(&[3, 4]), Ok(vec![3, 4]));
+        assert_eq!(layer.get_info().trainable_param, 4 * 20 + 30 + 28 + 2 * 8);
+        assert_eq!(layer.get_params_list().len(), 16);
+        assert_eq!(layer.get_params_list()[0],
+            LearnableParams::Sublayer(0, Box::new(LearnableParams::Sublayer(0, Box::new(LearnableParams::Weights)))));
+        check_gradients(&mut layer, input.clone());
+
+        let mut layer = TransformerEncoderBlock::new(4, 1, 6, 29).with_causal_mask(true);
+        layer.build(&[3, 4]).unwrap();
+        check_gradients(&mut layer, input);
+    }
+
+    #[test]
+    fn test_activations() {
+        let functions = [
+            ActivationFn::LeakyReLU {slope: 0.1},
+            ActivationFn::ELU {alpha: 0.7},
+            ActivationFn::SELU,
+            ActivationFn::GELU,
+            ActivationFn::Swish {beta: 1.5},
+            ActivationFn::Softplus,
+            ActivationFn::Mish,
+        ];
+        let input = Tensor::random_normal(vec![4, 5], 0.0, 3.0, 30);
+
+        for function in functions {
+            let mut layer = Activation::new(function);
+            assert_eq!(layer.build(&[5]), Ok(vec![5]));
+            check_gradients(&mut layer, input.clone());
+
+            // no overflow for large inputs
+            let output = layer.forward(Tensor::new(vec![-1000.0, 1000.0], vec![1, 2]), false);
+            assert!(output.data.iter().all(|x| x.is_finite()), "{:?} overflows", function);
+            let grad = layer.backward(&Tensor::one(vec![1, 2]));
+            assert!(grad.data.iter().all(|x| x.is_finite()), "{:?} gradient overflows", function);
+        }
+
+        // reference values
+        let gelu = ActivationFn::GELU;
+        assert!((gelu.value(1.0) - 0.841_344_746_068_542_9).abs() < 1e-15);
+        assert!((gelu.value(-2.0) + 0.045_500_263_896_358_42).abs() < 1e-15);
+        assert!((ActivationFn::Softplus.value(-800.0)).abs() < 1e-300);
+        assert_eq!(ActivationFn::Softplus.value(800.0), 800.0);
+        assert!((ActivationFn::SELU.value(-1.0) + 1.111_330_737_812_562_8).abs() < 1e-12);
+        assert_eq!(Activation::new(ActivationFn::LeakyReLU {slope: 0.2}).get_info().layer_type, "LeakyReLU 0.2");
+    }
+
+    #[test]
+    fn test_prelu() {
+        let mut layer = PReLU::new(0.25, DataFormat::ChannelsLast);
+        assert_eq!(layer.build(&[3]), Ok(vec![3]));
+        assert_eq!(layer.get_params_list(), vec![LearnableParams::Alpha]);
+        *layer.get_param(&LearnableParams::Alpha) = Tensor::new(vec![0.1, 0.2, 0.3], vec![1, 3]);
+
+        let input = Tensor::new(vec![-1.0, 2.0, -3.0,
+                                     4.0, -5.0, -6.0], vec![2, 3]);
+        let output = layer.forward(input, true);
+        let expected = [-0.1, 2.0, -0.9, 4.0, -1.0, -1.8];
+        assert!(output.data.iter().zip(expected).all(|(x, y)| (x - y).abs() < 1e-12));
+        layer.backward(&Tensor::one(vec![2, 3]));
+        assert_eq!(layer.get_grad(&LearnableParams::Alpha), &Tensor::new(vec![-1.0, -5.0, -9.0], vec![1, 3]));
+
+        // one slope per channel of images
+        let input = Tensor::random_normal(vec![2, 3, 2, 2], 0.0, 1.0, 31);
+        let mut layer = PReLU::new(0.25, DataFormat::ChannelsFirst);
+        assert_eq!(layer.build(&[3, 2, 2]), Ok(vec![3, 2, 2]));
+        assert_eq!(layer.get_info().trainable_param, 3);
+        check_gradients(&mut layer, input);
+    }
+}
@@ -49,7 +49,7 @@ mod metrics_tests {
                                                         y_pred.clone());
 
         let rec_score = cm.recall_score(1);
-        let result = 0.7;
+        let result = 1.0;
         assert_eq!(utils::round_f64(rec_score, 1), result);
     }
 
@@ -61,7 +61,7 @@ mod metrics_tests {
                                                         y_pred.clone());
 
         let pre_score = cm.precision_score(1);
-        let result = 1.0;
+        let result = 0.7;
         assert_eq!(utils::round_f64(pre_score, 1), result);
     }
 
@@ -76,4 +76,187 @@ mod metrics_tests {
         let result = 0.8;
         assert_eq!(utils::round_f64(f1_score, 1), result);
     }
+
+    fn setup_multiclass() -> confusion_matrix::ConfusionMatrix {
+        // true classes:      0, 0, 1, 1, 1, 2
+        // predicted classes: 0, 1, 1, 1, 2, 2
+        let y_true = Tensor::new(vec![1.0, 0.0, 0.0,
+                                      1.0, 0.0, 0.0,
+                                      0.0, 1.0, 0.0,
+                                      0.0, 1.0, 0.0,
+                                      0.0, 1.0, 0.0,
+                                      0.0, 0.0, 1.0], vec![6, 3]);
+
+        let y_pred = Tensor::new(vec![0.8, 0.1, 0.1,
+                                      0.2, 0.7, 0.1,
+                                      0.1, 0.8, 0.1,
+                                      0.3, 0.6, 0.1,
+                                      0.1, 0.2, 0.7,
+                                      0.0, 0.1, 0.9], vec![6, 3]);
+
+        confusion_matrix::ConfusionMatrix::new(y_true, y_pred)
+    }
+
+    #[test]
+    fn test_averaged_recall() {
+        let cm = setup_multiclass();
+
+        assert_eq!(utils::round_f64(cm.recall(&Average::Class(0)).unwrap(), 3), 0.5);
+        assert_eq!(utils::round_f64(cm.recall(&Average::Macro).unwrap(), 3), 0.722);
+        assert_eq!(utils::round_f64(cm.recall(&Average::Micro).unwrap(), 3), 0.667);
+        assert_eq!(utils::round_f64(cm.recall(&Average::Weighted).unwrap(), 3), 0.667);
+    }
+
+    #[test]
+    fn test_averaged_precision() {
+        let cm = setup_multiclass();
+
+        assert_eq!(utils::round_f64(cm.precision(&Average::Class(2)).unwrap(), 3), 0.5);
+        assert_eq!(utils::round_f64(cm.precision(&Average::Macro).unwrap(), 3), 0.722);
+        assert_eq!(utils::round_f64(cm.precision(&Average::Micro).unwrap(), 3), 0.667);
+        assert_eq!(utils::round_f64(cm.precision(&Average::Weighted).unwrap(), 3), 0.75);
+    }
+
+    #[test]
+    fn test_unknown_class() {
+        let cm = setup_multiclass();
+
+        let error = cm.f1(&Average::Class(3)).unwrap_err();
+        assert_eq!(error, MetricError::UnknownClass { class: 3, classes: 3 });
+        assert_eq!(error.to_string(), "Unknown class 3: the labels only have 3 classes.");
+        assert_eq!(cm.recall(&Average::Class(5)), Err(MetricError::UnknownClass { class: 5, classes: 3 }));
+        assert!(cm.precision(&Average::Class(2)).is_ok());
+    }
+
+    #[test]
+    fn test_classification_report() {
+        let cm = setup_multiclass();
+
+        let report = cm.classification_report();
+
+        assert_eq!(report.classes.len(), 3);
+        assert_eq!(report.classes.iter().map(|c| c.support).collect::<Vec<_>>(), vec![2, 3, 1]);
+        assert_eq!(utils::round_f64(report.classes[1].f1, 3), 0.667);
+        assert_eq!(utils::round_f64(report.macro_avg.f1, 3), 0.667);
+        assert_eq!(utils::round_f64(report.accuracy, 3), 0.667);
+    }
+
+    #[test]
+    fn test_class_never_predicted() {
+        // class 0 is never predicted: its precision is 0 instead of NaN
+        let y_true = Tensor::new(vec![1.0, 0.0, 0.0, 1.0], vec![2, 2]);
+        let y_pred = Tensor::new(vec![0.4, 0.6, 0.1, 0.9], vec![2, 2]);
+
+        let cm = confusion_matrix::ConfusionMatrix::new(y_true, y_pred);
+
+        assert_eq!(cm.precision_score(0), 0.0);
+        assert_eq!(cm.f1_score(0), 0.0);
+    }
+
+    #[test]
+    fn test_regression_metrics() {
+        // second output is perfectly predicted
+        let y_true = Tensor::new(vec![3.0, 1.0,
+                                     -0.5, 2.0,
+                                      2.0, 3.0,
+                                      7.0, 4.0], vec![4, 2]);
+
+        let y_pred = Tensor::new(vec![2.5, 1.0,
+                                      0.0, 2.0,
+                                      2.0, 3.0,
+                                      8.0, 4.0], vec![4, 2]);
+
+        let mut stats = regression::RegressionStats::new(2);
+        // update by batch of 2 rows
+        stats.update(&y_true.get_rows(&[0, 1]), &y_pred.get_rows(&[0, 1]));
+        stats.update(&y_true.get_rows(&[2, 3]), &y_pred.get_rows(&[2, 3]));
+
+        assert_eq!(utils::round_vector(stats.mae(), 3), vec![0.5, 0.0]);
+        assert_eq!(utils::round_vector(stats.rmse(), 3), vec![0.612, 0.0]);
+        assert_eq!(utils::round_vector(stats.r2_score(), 3), vec![0.949, 1.0]);
+        assert_eq!(utils::round_vector(stats.explained_variance_score(), 3), vec![0.957, 1.0]);
+        assert_eq!(utils::round_vector(stats.mape(), 3), vec![0.327, 0.0]);
+        assert_eq!(regression::average(&stats.mae()), 0.25);
+    }
+
+    #[test]
+    fn test_regression_metrics_large_offset() {
+        // same values as `test_regression_metrics` around 1e8
+        let offset = |values: Vec<f64>| Tensor::new(values.iter().map(|x| x + 1e8).collect(), vec![4, 2]);
+        let y_true = offset(vec![3.0, 1.0, -0.5, 2.0, 2.0, 3.0, 7.0, 4.0]);
+        let y_pred = offset(vec![2.5, 1.0, 0.0, 2.0, 2.0, 3.0, 8.0, 4.0]);
+
+        let mut stats = regression::RegressionStats::new(2);
+        stats.update(&y_true.get_rows(&[0, 1]), &y_pred.get_rows(&[0, 1]));
+        stats.update(&y_true.get_rows(&[2, 3]), &y_pred.get_rows(&[2, 3]));
+
+        assert_eq!(utils::round_vector(stats.r2_score(), 3), vec![0.949, 1.0]);
+        assert_eq!(utils::round_vector(stats.explained_variance_score(), 3), vec![0.957, 1.0]);
+    }
+
+    #[test]
+    fn test_binary_probabilistic_metrics() {
+        let y_true = Tensor::new(vec![0.0, 0.0, 1.0, 1.0], vec![4, 1]);
+        let y_pred = Tensor::new(vec![0.1, 0.4, 0.35, 0.8], vec![4, 1]);
+
+        let mut scores = probabilistic::ProbabilityScores::new(1);
+        scores.update(&y_true, &y_pred);
+
+        assert_eq!(utils::round_f64(scores.roc_auc(&Average::Macro).unwrap(), 3), 0.75);
+        assert_eq!(utils::round_f64(scores.average_precision(&Average::Macro).unwrap(), 3), 0.833);
+        assert_eq!(utils::round_f64(scores.log_loss(), 3), 0.472);
+        assert_eq!(utils::round_f64(scores.brier_score(), 3), 0.158);
+
+        let curve = scores.roc_curve(1);
+        assert_eq!(curve.false_positive_rate, vec![0.0, 0.0, 0.5, 0.5, 1.0]);
+        assert_eq!(curve.true_positive_rate, vec![0.0, 0.5, 0.5, 1.0, 1.0]);
+        assert_eq!(curve.thresholds[1..].to_vec(), vec![0.8, 0.4, 0.35, 0.1]);
+
+        let curve = scores.precision_recall_curve(1);
+        assert_eq!(curve.recall, vec![0.0, 0.5, 0.5, 1.0, 1.0]);
+        assert_eq!(utils::round_vector(curve.precision, 3), vec![1.0, 1.0, 0.5, 0.667, 0.5]);
+    }
+
+    #[test]
+    fn test_one_vs_rest_roc_auc() {
+        // same problem as the binary test, one-hot encoded
+        let y_true = Tensor::new(vec![1.0, 0.0,
+                                      1.0, 0.0,
+                                      0.0, 1.0,
+                                      0.0, 1.0], vec![4, 2]);
+        let y_pred = Tensor::new(vec![0.9, 0.1,
+                                      0.6, 0.4,
+                                      0.65, 0.35,
+                                      0.2, 0.8], vec![4, 2]);
+
+        let mut scores = probabilistic::ProbabilityScores::new(2);
+        scores.update(&y_true.get_rows(&[0, 1]), &y_pred.get_rows(&[0, 1]));
+        scores.update(&y_true.get_rows(&[2, 3]), &y_pred.get_rows(&[2, 3]));
+
+        assert_eq!(utils::round_f64(scores.roc_auc(&Average::Class(0)).unwrap(), 3), 0.75);
+        assert_eq!(utils::round_f64(scores.roc_auc(&Average::Class(1)).unwrap(), 3), 0.75);
+        assert_eq!(utils::round_f64(scores.roc_auc(&Average::Macro).unwrap(), 3), 0.75);
+        assert_eq!(utils::round_f64(scores.roc_auc(&Average::Weighted).unwrap(), 3), 0.75);
+        assert_eq!(utils::round_f64(scores.log_loss(), 3), 0.472);
+        // Brier score sums the squared errors of both classes
+        assert_eq!(utils::round_f64(scores.brier_score(), 3), 0.316);
+    }
+
+    #[test]
+    fn test_probabilistic_unknown_class() {
+        let y_true = Tensor::new(vec![1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0], vec![3, 3]);
+        let y_pred = Tensor::new(vec![0.6, 0.3, 0.1, 0.2, 0.5, 0.3, 0.1, 0.2, 0.7], vec![3, 3]);
+        let mut scores = probabilistic::ProbabilityScores::new(3);
+        scores.update(&y_true, &y_pred);
+
+        assert_eq!(scores.roc_auc(&Average::Class(3)), Err(MetricError::UnknownClass { class: 3, classes: 3 }));
+        assert_eq!(scores.average_precision(&Average::Class(4)), Err(MetricError::UnknownClass { class: 4, classes: 3 }));
+        assert!(scores.roc_auc(&Average::Class(2)).is_ok());
+
+        // a single target column has a negative (0) and a positive (1) class
+        let mut scores = probabilistic::ProbabilityScores::new(1);
+        scores.update(&Tensor::new(vec![0.0, 1.0], vec![2, 1]), &Tensor::new(vec![0.3, 0.6], vec![2, 1]));
+        assert_eq!(scores.roc_auc(&Average::Class(0)), Ok(1.0));
+        assert_eq!(scores.roc_auc(&Average::Class(2)), Err(MetricError::UnknownClass { class: 2, classes: 2 }));
+    }
 }
@@ -0,0 +1,107 @@
+#[cfg(test)]
+mod regularizers_tests {
+    use newron::dataset::Dataset;
+    use newron::initializers::Initializer;
+    use newron::layers::LayerEnum;
+    use newron::layers::activity_regularization::ActivityRegularization;
+    use newron::layers::dense::Dense;
+    use newron::layers::layer::{Layer, LearnableParams};
+    use newron::loss::mse::MSE;
+    use newron::optimizers::sgd::SGD;
+    use newron::regularizers::Regularizer;
+    use newron::sequential::Sequential;
+    use newron::tensor::Tensor;
+
+    #[test]
+    fn test_penalties() {
+        let w = Tensor::new(vec![-2.0, 0.0, 1.0], vec![1, 3]);
+
+        assert_eq!(Regularizer::L1(0.5).penalty(&w), 1.5);
+        assert_eq!(Regularizer::L1(0.5).gradient(&w), Tensor::new(vec![-0.5, 0.0, 0.5], vec![1, 3]));
+        assert_eq!(Regularizer::L2(0.5).penalty(&w), 2.5);
+        assert_eq!(Regularizer::L2(0.5).gradient(&w), Tensor::new(vec![-2.0, 0.0, 1.0], vec![1, 3]));
+
+        let elastic_net = Regularizer::ElasticNet { l1: 0.5, l2: 0.5 };
+        assert_eq!(elastic_net.penalty(&w), 4.0);
+        assert_eq!(elastic_net.gradient(&w), Tensor::new(vec![-2.5, 0.0, 1.5], vec![1, 3]));
+    }
+
+    #[test]
+    fn test_dense_regularizers() {
+        let input = Tensor::random_normal(vec![4, 3], 0.0, 1.0, 1);
+        let gradient = Tensor::random_normal(vec![4, 2], 0.0, 1.0, 2);
+        let regularizer = Regularizer::ElasticNet { l1: 0.01, l2: 0.1 };
+
+        let mut plain = Dense::new(3, 2, 3).with_bias_initializer(Initializer::Ones);
+        let mut regularized = Dense::new(3, 2, 3)
+            .with_bias_initializer(Initializer::Ones)
+            .with_kernel_regularizer(regularizer)
+            .with_bias_regularizer(Regularizer::L2(0.1));
+        for layer in [&mut plain, &mut regularized] {
+            layer.build(&[3]).unwrap();
+            layer.forward(input.clone(), true);
+            layer.backward(&gradient);
+        }
+
+        let weights = plain.get_param(&LearnableParams::Weights).clone();
+        let biases = plain.get_param(&LearnableParams::Biases).clone();
+        assert_eq!(plain.get_regularization_loss(), 0.0);
+        assert_eq!(regularized.get_regularization_loss(), regularizer.penalty(&weights) + 0.2);
+
+        // the gradients of the penalties are added to the gradients of the loss
+        assert_eq!(regularized.get_grad(&LearnableParams::Weights),
+            &(plain.get_grad(&LearnableParams::Weights) + &regularizer.gradient(&weights)));
+        assert_eq!(regularized.get_grad(&LearnableParams::Biases),
+            &(plain.get_grad(&LearnableParams::Biases) + &(0.2 * biases)));
+    }
+
+    #[test]
+    fn test_activity_regularization() {
+        let mut layer = ActivityRegularization::new(Regularizer::L1(0.1));
+        let input = Tensor::new(vec![1.0, -2.0, 0.0, 3.0], vec![2, 2]);
+
+        // identity whose penalty is averaged over the batch
+        assert_eq!(layer.forward(input.clone(), true), input);
+        assert!((layer.get_regularization_loss() - 0.3).abs() < 1e-12);
+        let grad = layer.backward(&Tensor::one(vec![2, 2]));
+        assert_eq!(grad, Tensor::new(vec![1.05, 0.95, 1.0, 1.05], vec![2, 2]));
+    }
+
+    #[test]
+    fn test_fit_with_regularizers() {
+        let data = (0..64).map(|i| {
+            let x = (i as f64 / 8.0).sin();
+            let y = (i as f64 / 5.0).cos();
+            vec![x, y, 0.5 * x - y]
+        }).collect();
+        let dataset = Dataset::from_raw_data(data).unwrap();
+
+        let weights_norm = |regularizer: Option<Regularizer>| {
+            let mut model = Sequential::new();
+            model.add(LayerEnum::Dense {
+                input_units: Some(2),
+                output_units: 8,
+                kernel_initializer: Initializer::GlorotUniform,
+                bias_initializer: Initializer::Zeros,
+                kernel_regularizer: regularizer,
+                bias_regularizer: None,
+            });
+            model.add(LayerEnum::ActivityRegularization { regularizer: Regularizer::L2(0.001) });
+            model.add(LayerEnum::Dense {input_units: None, output_units: 1, kernel_initializer: Initializer::GlorotUniform, bias_initializer: Initializer::Zeros, kernel_regularizer: regularizer, bias_regularizer: None});
+            model.compile(MSE{}, SGD::new(0.002), vec![]).unwrap();
+            model.fit(&dataset, 300, false);
+
+            let penalty: f64 = model.layers.iter().map(|layer| layer.get_regularization_loss()).sum();
+            let norm: f64 = [0, 2].iter()
+                .map(|i| model.layers[*i].get_param(&LearnableParams::Weights).data.iter().map(|w| w * w).sum::<f64>())
+                .sum();
+            (norm, penalty)
+        };
+
+        let (plain_norm, _) = weights_norm(None);
+        let (regularized_norm, penalty) = weights_norm(Some(Regularizer::L2(1.0)));
+        // weight decay keeps the weights smaller
+        assert!(regularized_norm < 0.5 * plain_norm, "{} >= {}", regularized_norm, plain_norm);
+        assert!(penalty >= regularized_norm);
+    }
+}
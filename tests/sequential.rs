@@ -1,22 +1,735 @@
 #[cfg(test)]
 mod sequential_tests {
-    use newron::sequential::Sequential;
-    use newron::layers::LayerEnum::*;
-    use newron::loss::{mse::MSE};
+    use newron::sequential::{Sequential, ModelError};
+    use newron::dataset::{Dataset, RowType, ColumnType};
+    use newron::layers::LayerEnum::{self, *};
+    use newron::layers::{DataFormat, Padding};
+    use newron::layers::bidirectional::MergeMode;
+    use newron::layers::recurrent::{Recurrent, CellType};
+    use newron::layers::layer::{Layer, LayerInfo, LearnableParams};
+    use newron::loss::{mse::MSE, loss::Loss, categorical_entropy::CategoricalEntropy};
     use newron::optimizers::sgd::SGD;
-    use newron::metrics::Metric;
+    use newron::metrics::{Average, Metric, MetricError};
+    use newron::metrics::streaming_metric::StreamingMetric;
+    use newron::tensor::Tensor;
+    use newron::metrics::confusion_matrix::ConfusionMatrix;
+    use newron::utils;
+    use std::cell::RefCell;
+    use std::rc::Rc;
     
     #[test]
     fn test_sequential_stacking() {
         let mut model = Sequential::new();
 
-        model.add(Dense {input_units: 20, output_units: 100});
+        model.add(LayerEnum::dense(Some(20), 100));
         model.add(ReLU);
 
         model.compile(MSE{},
             SGD::new(0.002),
-            vec![Metric::Accuracy]);
+            vec![Metric::Accuracy]).unwrap();
 
         assert_eq!(model.layers.len(), 2);
     }
+
+    #[test]
+    fn test_shape_propagation() {
+        let mut model = Sequential::new();
+        model.add(LayerEnum::dense(Some(4), 6));
+        model.add(ReLU);
+        model.add(Dropout {prob: 0.5});
+        model.add(Reshape {target_shape: vec![3, 2]});
+        model.add(Flatten);
+        model.add(LayerEnum::dense(None, 2));
+        model.add(Softmax);
+
+        model.compile(CategoricalEntropy{},
+            SGD::new(0.1),
+            vec![]).unwrap();
+
+        let shapes: Vec<Vec<usize>> = model.layers.iter().map(|l| l.get_info().output_shape).collect();
+        assert_eq!(shapes, vec![vec![6], vec![6], vec![6], vec![3, 2], vec![6], vec![2], vec![2]]);
+    }
+
+    #[test]
+    fn test_shape_mismatch() {
+        let mut model = Sequential::new();
+        model.add(LayerEnum::dense(Some(4), 6));
+        model.add(LayerEnum::dense(Some(5), 2));
+
+        let error = model.compile(MSE{}, SGD::new(0.1), vec![]).unwrap_err();
+        assert_eq!(error, ModelError::IncompatibleShape {
+            layer: 1,
+            layer_type: "Dense".to_string(),
+            input_shape: vec![6],
+            message: "expected an input shape [5], got [6]".to_string(),
+        });
+        assert_eq!(error.to_string(), "Layer 1 (Dense) with input shape [6]: expected an input shape [5], got [6].");
+
+        // Dense layers need a 1D input
+        let mut model = Sequential::new();
+        model.set_input_shape(vec![4, 2]);
+        model.add(ReLU);
+        model.add(LayerEnum::dense(None, 2));
+        assert!(matches!(model.compile(MSE{}, SGD::new(0.1), vec![]),
+            Err(ModelError::IncompatibleShape { layer: 1, .. })));
+
+        // the input shape of the first layer cannot be inferred
+        let mut model = Sequential::new();
+        model.add(LayerEnum::dense(None, 2));
+        assert_eq!(model.compile(MSE{}, SGD::new(0.1), vec![]),
+            Err(ModelError::UnknownInputShape { layer: 0, layer_type: "Dense".to_string() }));
+    }
+
+    #[test]
+    fn test_pool2d_explicit_padding() {
+        // windows at the edges would only cover padding
+        let mut model = Sequential::new();
+        model.set_input_shape(vec![1, 4, 4]);
+        model.add(MaxPool2D { pool_size: (2, 2), stride: (2, 2), padding: Padding::Explicit(2, 0), data_format: DataFormat::ChannelsFirst });
+        assert_eq!(model.compile(MSE{}, SGD::new(0.1), vec![]).err(), Some(ModelError::IncompatibleShape {
+            layer: 0,
+            layer_type: "MaxPool2D 2x2".to_string(),
+            input_shape: vec![1, 4, 4],
+            message: "explicit padding (2) must be at most half of the pool size (2)".to_string(),
+        }));
+
+        let mut model = Sequential::new();
+        model.set_input_shape(vec![1, 4, 4]);
+        model.add(AvgPool2D { pool_size: (2, 2), stride: (2, 2), padding: Padding::Explicit(1, 1), data_format: DataFormat::ChannelsFirst });
+        model.compile(MSE{}, SGD::new(0.1), vec![]).unwrap();
+        assert_eq!(model.layers[0].get_info().output_shape, vec![1, 3, 3]);
+        let output = model.predict_tensor(Tensor::one(vec![1, 1, 4, 4]));
+        assert_eq!(output, Tensor::one(vec![1, 1, 3, 3]));
+    }
+
+    #[test]
+    fn test_layer_seeds() {
+        let weights = |seed: u32| {
+            let mut model = Sequential::new();
+            model.set_seed(seed);
+            model.add(LayerEnum::dense(Some(4), 4));
+            model.add(LayerEnum::dense(None, 4));
+            model.compile(MSE{}, SGD::new(0.1), vec![]).unwrap();
+            (0..2).map(|i| model.layers[i].get_param(&LearnableParams::Weights).clone()).collect::<Vec<Tensor>>()
+        };
+
+        // layers of the same shape do not start with the same weights
+        let first = weights(7);
+        assert_ne!(first[0], first[1]);
+        // the same seed gives the same model, another seed another one
+        assert_eq!(weights(7), first);
+        assert_ne!(weights(8)[0], first[0]);
+    }
+
+    #[test]
+    fn test_set_input_shape() {
+        // each row of 6 features is a sequence of 3 steps with 2 channels
+        let mut data = Vec::new();
+        for i in 0..30 {
+            let x = (i % 6) as f64 / 6.0;
+            data.push(vec![x, 0.0, 1.0 - x, 0.5, x, 1.0, (i % 2) as f64]);
+        }
+        let mut dataset = Dataset::from_raw_data(data).unwrap();
+        dataset.one_hot_encode(6);
+
+        let mut model = Sequential::new();
+        model.set_input_shape(vec![3, 2]);
+        model.add(Conv1D {
+            input_channels: 2,
+            filters: 4,
+            kernel_size: 2,
+            stride: 1,
+            padding: Padding::Causal,
+            dilation: 1,
+        });
+        model.add(Flatten);
+        model.add(LayerEnum::dense(None, 2));
+
+        model.compile(CategoricalEntropy{},
+            SGD::new(0.1),
+            vec![]).unwrap();
+
+        let shapes: Vec<Vec<usize>> = model.layers.iter().map(|l| l.get_info().output_shape).collect();
+        assert_eq!(shapes, vec![vec![3, 4], vec![12], vec![2]]);
+
+        model.fit(&dataset, 2, false);
+        let evaluation = model.evaluate(&dataset, RowType::Train).unwrap();
+        assert!(evaluation.loss.is_finite());
+    }
+
+    #[test]
+    #[should_panic(expected = "The model outputs 3 values per row but the dataset has 2 targets.")]
+    fn test_fit_wrong_targets() {
+        let mut dataset = Dataset::from_raw_data(vec![vec![0.0, 1.0, 0.0], vec![1.0, 0.0, 1.0]]).unwrap();
+        dataset.one_hot_encode(2);
+
+        let mut model = Sequential::new();
+        model.add(LayerEnum::dense(Some(2), 3));
+        model.compile(MSE{}, SGD::new(0.1), vec![]).unwrap();
+        model.fit(&dataset, 1, false);
+    }
+
+    #[test]
+    fn test_fit_with_reshape() {
+        // each row of 4 features is a 2x2 image with a single channel
+        let mut data = Vec::new();
+        for i in 0..40 {
+            let x = (i % 5) as f64 / 5.0;
+            data.push(vec![x, 1.0 - x, x * x, 0.5, (i % 2) as f64]);
+        }
+        let mut dataset = Dataset::from_raw_data(data).unwrap();
+        dataset.one_hot_encode(4);
+
+        let mut model = Sequential::new();
+        model.add(Reshape {target_shape: vec![2, 2, 1]});
+        model.add(Conv2D {
+            input_channels: 1,
+            filters: 3,
+            kernel_size: (2, 2),
+            stride: (1, 1),
+            padding: Padding::Valid,
+            dilation: (1, 1),
+            data_format: DataFormat::ChannelsLast,
+        });
+        model.add(Flatten);
+        model.add(LayerEnum::dense(Some(3), 2));
+
+        model.compile(CategoricalEntropy{},
+            SGD::new(0.1),
+            vec![Metric::Accuracy]).unwrap();
+
+        // the input shape is only known once the dataset is given
+        assert!(model.layers[1].get_info().output_shape.is_empty());
+        model.fit(&dataset, 2, false);
+
+        let shapes: Vec<Vec<usize>> = model.layers.iter().map(|l| l.get_info().output_shape).collect();
+        assert_eq!(shapes, vec![vec![2, 2, 1], vec![1, 1, 3], vec![3], vec![2]]);
+        assert_eq!(model.predict(&vec![0.2, 0.8, 0.04, 0.5]).shape, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_embedding_sparse_update() {
+        // sequences of 2 indices, only the indices 0 to 3 are used
+        let mut data = Vec::new();
+        for i in 0..20 {
+            data.push(vec![(i % 4) as f64, ((i + 1) % 4) as f64, (i % 2) as f64]);
+        }
+        let mut dataset = Dataset::from_raw_data(data).unwrap();
+        dataset.one_hot_encode(2);
+
+        let mut model = Sequential::new();
+        model.set_input_shape(vec![2]);
+        model.add(Embedding {vocab_size: 10, dim: 3});
+        model.add(Flatten);
+        model.add(LayerEnum::dense(None, 2));
+        model.compile(CategoricalEntropy{},
+            SGD::new(0.1),
+            vec![]).unwrap();
+
+        let before = model.layers[0].get_param(&LearnableParams::Weights).clone();
+        model.fit(&dataset, 1, false);
+        let after = model.layers[0].get_param(&LearnableParams::Weights).clone();
+
+        assert_ne!(before.data[..12], after.data[..12]);
+        assert_eq!(before.data[12..], after.data[12..]);
+    }
+
+    #[test]
+    fn test_fit_recurrent() {
+        // classify whether a sequence of 4 steps is increasing
+        let mut data = Vec::new();
+        for i in 0..40 {
+            let start = (i % 5) as f64 / 5.0;
+            let step = if i % 2 == 0 { 0.1 } else { -0.1 };
+            data.push(vec![start, start + step, start + 2.0 * step, start + 3.0 * step, (i % 2) as f64]);
+        }
+        let mut dataset = Dataset::from_raw_data(data).unwrap();
+        dataset.one_hot_encode(4);
+
+        let mut model = Sequential::new();
+        model.set_input_shape(vec![4, 1]);
+        model.add(Bidirectional {
+            layer: Box::new(LSTM {input_features: None, units: 3, return_sequences: true, initial_state: None}),
+            merge_mode: MergeMode::Concat,
+        });
+        model.add(GRU {input_features: None, units: 4, return_sequences: false, initial_state: None});
+        model.add(LayerEnum::dense(None, 2));
+        model.compile(CategoricalEntropy{},
+            SGD::new(0.5),
+            vec![]).unwrap();
+
+        let shapes: Vec<Vec<usize>> = model.layers.iter().map(|l| l.get_info().output_shape).collect();
+        assert_eq!(shapes, vec![vec![4, 6], vec![4], vec![2]]);
+
+        let loss_before = model.evaluate(&dataset, RowType::Train).unwrap().loss;
+        model.fit(&dataset, 20, false);
+        assert!(model.evaluate(&dataset, RowType::Train).unwrap().loss < loss_before);
+    }
+
+    #[test]
+    fn test_recurrent_initial_state() {
+        let input = Tensor::random_normal(vec![2, 3, 2], 0.0, 1.0, 1);
+        let state = Tensor::new(vec![0.5, -0.5, 1.0], vec![1, 3]);
+
+        let outputs: Vec<Tensor> = [None, Some(vec![state.clone(), state.clone()])].iter().map(|initial_state| {
+            let mut model = Sequential::new();
+            model.set_input_shape(vec![3, 2]);
+            model.add(LSTM {input_features: None, units: 3, return_sequences: false, initial_state: initial_state.clone()});
+            model.compile(MSE{}, SGD::new(0.1), vec![]).unwrap();
+            model.layers[0].forward(input.clone(), false)
+        }).collect();
+        assert_ne!(outputs[0], outputs[1]);
+
+        // same output as the layer built directly
+        let mut lstm = Recurrent::new(CellType::LSTM, 2, 3, 0).with_initial_state(vec![state.clone(), state.clone()]);
+        let mut model = Sequential::new();
+        model.set_input_shape(vec![3, 2]);
+        model.add(LSTM {input_features: None, units: 3, return_sequences: false, initial_state: None});
+        model.compile(MSE{}, SGD::new(0.1), vec![]).unwrap();
+        for param in [LearnableParams::Weights, LearnableParams::RecurrentWeights] {
+            *lstm.get_param(&param) = model.layers[0].get_param(&param).clone();
+        }
+        assert_eq!(lstm.forward(input, false), outputs[1]);
+
+        // a GRU has a single state
+        let mut model = Sequential::new();
+        model.set_input_shape(vec![3, 2]);
+        model.add(GRU {input_features: None, units: 3, return_sequences: false, initial_state: Some(vec![state.clone(), state])});
+        assert_eq!(model.compile(MSE{}, SGD::new(0.1), vec![]).err(), Some(ModelError::IncompatibleShape {
+            layer: 0,
+            layer_type: "GRU".to_string(),
+            input_shape: vec![3, 2],
+            message: "expected 1 initial state tensors of shape [batch, 3]".to_string(),
+        }));
+    }
+
+    #[test]
+    fn test_fit_transformer() {
+        // classify whether the token 3 appears in a sequence of 4 tokens
+        let mut data = Vec::new();
+        for i in 0..40 {
+            let mut tokens: Vec<f64> = (0..4).map(|t| ((i + t * 2) % 3) as f64).collect();
+            if i % 2 == 0 {
+                tokens[i % 4] = 3.0;
+            }
+            tokens.push((i % 2 == 0) as usize as f64);
+            data.push(tokens);
+        }
+        let mut dataset = Dataset::from_raw_data(data).unwrap();
+        dataset.one_hot_encode(4);
+
+        let mut model = Sequential::new();
+        model.set_input_shape(vec![4]);
+        model.add(Embedding {vocab_size: 4, dim: 4});
+        model.add(TransformerEncoderBlock {model_dim: None, heads: 2, feed_forward_dim: 8, causal: false, mask_padding: false});
+        model.add(MultiHeadAttention {model_dim: None, heads: 1, causal: true, mask_padding: false});
+        model.add(Flatten);
+        model.add(LayerEnum::dense(None, 2));
+        model.compile(CategoricalEntropy{},
+            SGD::new(0.05),
+            vec![]).unwrap();
+
+        let shapes: Vec<Vec<usize>> = model.layers.iter().map(|l| l.get_info().output_shape).collect();
+        assert_eq!(shapes, vec![vec![4, 4], vec![4, 4], vec![4, 4], vec![16], vec![2]]);
+
+        let loss_before = model.evaluate(&dataset, RowType::Train).unwrap().loss;
+        model.fit(&dataset, 20, false);
+        assert!(model.evaluate(&dataset, RowType::Train).unwrap().loss < loss_before);
+    }
+
+    #[test]
+    fn test_fit_prelu() {
+        let mut data = Vec::new();
+        for i in 0..40 {
+            let x = (i % 8) as f64 / 4.0 - 1.0;
+            data.push(vec![x, -x, (x > 0.0) as usize as f64]);
+        }
+        let mut dataset = Dataset::from_raw_data(data).unwrap();
+        dataset.one_hot_encode(2);
+
+        let mut model = Sequential::new();
+        model.add(LayerEnum::dense(Some(2), 4));
+        model.add(PReLU {initial_slope: 0.25, data_format: DataFormat::ChannelsLast});
+        model.add(LayerEnum::dense(None, 2));
+        model.compile(CategoricalEntropy{},
+            SGD::new(0.1),
+            vec![]).unwrap();
+
+        // the slopes are updated by the optimizer
+        model.fit(&dataset, 5, false);
+        let slopes = model.layers[1].get_param(&LearnableParams::Alpha).clone();
+        assert_eq!(slopes.shape, vec![1, 4]);
+        assert!(slopes.data.iter().any(|slope| *slope != 0.25));
+    }
+
+    #[test]
+    fn test_evaluate_by_batch() {
+        // 300 rows so the evaluation is done with several batches
+        let mut data = Vec::new();
+        for i in 0..300 {
+            let x = i as f64 / 300.0;
+            data.push(vec![x, 1.0 - x, (i % 3) as f64]);
+        }
+        let mut dataset = Dataset::from_raw_data(data).unwrap();
+        dataset.one_hot_encode(2);
+
+        let mut model = Sequential::new();
+        model.add(LayerEnum::dense(Some(2), 3));
+        model.compile(CategoricalEntropy{},
+            SGD::new(0.1),
+            vec![Metric::Accuracy]).unwrap();
+
+        let evaluation = model.evaluate(&dataset, RowType::Train).unwrap();
+
+        // Evaluation by batch must match the evaluation on the whole dataset
+        let x = dataset.get_tensor(RowType::Train, ColumnType::Feature);
+        let y = dataset.get_tensor(RowType::Train, ColumnType::Target);
+        let predictions = model.predict_tensor(x);
+        let loss = CategoricalEntropy{}.compute_loss(&y, &predictions);
+        let accuracy = ConfusionMatrix::new(y, predictions).accuracy_score();
+
+        assert_eq!(utils::round_f64(evaluation.loss, 8), utils::round_f64(loss, 8));
+        assert_eq!(evaluation.metrics.len(), 1);
+        assert_eq!(evaluation.get("Accuracy"), Some(accuracy));
+    }
+
+    #[test]
+    fn test_evaluate_unknown_class() {
+        let data = (0..20).map(|i| vec![i as f64 / 20.0, (i % 3) as f64]).collect();
+        let mut dataset = Dataset::from_raw_data(data).unwrap();
+        dataset.one_hot_encode(1);
+        dataset.split_train_test(0.5, false);
+
+        let mut model = Sequential::new();
+        model.add(LayerEnum::dense(Some(1), 3));
+        model.compile(CategoricalEntropy{},
+            SGD::new(0.1),
+            vec![Metric::Recall(Average::Class(5)), Metric::RocAuc(Average::Class(3))]).unwrap();
+
+        assert_eq!(model.evaluate(&dataset, RowType::Train).err(), Some(MetricError::UnknownClass { class: 5, classes: 3 }));
+        // the error is reported instead of the test metrics
+        model.fit(&dataset, 1, true);
+
+        model.compile(CategoricalEntropy{}, SGD::new(0.1), vec![Metric::RocAuc(Average::Class(3))]).unwrap();
+        assert_eq!(model.evaluate(&dataset, RowType::Test).err(), Some(MetricError::UnknownClass { class: 3, classes: 3 }));
+    }
+
+    // Proportion of observations whose true class is among the `k`
+    // classes with the highest predicted values
+    struct TopKAccuracy {
+        k: usize,
+        correct: usize,
+        total: usize,
+    }
+
+    impl StreamingMetric for TopKAccuracy {
+        fn name(&self) -> String {
+            format!("Top-{} Accuracy", self.k)
+        }
+
+        fn update(&mut self, y_true: &Tensor, y_pred: &Tensor) {
+            for row in 0..y_true.shape[0] {
+                let true_class = (0..y_true.shape[1]).find(|c| y_true.get_value(row, *c) == 1.0).unwrap();
+                let true_value = y_pred.get_value(row, true_class);
+                let rank = (0..y_pred.shape[1]).filter(|c| y_pred.get_value(row, *c) > true_value).count();
+                if rank < self.k {
+                    self.correct += 1;
+                }
+            }
+            self.total += y_true.shape[0];
+        }
+
+        fn reset(&mut self) {
+            self.correct = 0;
+            self.total = 0;
+        }
+
+        fn result(&self) -> f64 {
+            self.correct as f64 / self.total as f64
+        }
+    }
+
+    #[test]
+    fn test_custom_metric() {
+        let mut data = Vec::new();
+        for i in 0..200 {
+            let x = i as f64 / 200.0;
+            data.push(vec![x, 1.0 - x, (i % 3) as f64]);
+        }
+        let mut dataset = Dataset::from_raw_data(data).unwrap();
+        dataset.one_hot_encode(2);
+
+        let mut model = Sequential::new();
+        model.add(LayerEnum::dense(Some(2), 3));
+        model.compile(CategoricalEntropy{},
+            SGD::new(0.1),
+            vec![Metric::Accuracy,
+                 Metric::Custom(Box::new(TopKAccuracy {k: 1, correct: 0, total: 0})),
+                 Metric::Custom(Box::new(TopKAccuracy {k: 3, correct: 0, total: 0}))]).unwrap();
+
+        // metrics are reset between evaluations
+        model.evaluate(&dataset, RowType::Train).unwrap();
+        let evaluation = model.evaluate(&dataset, RowType::Train).unwrap();
+
+        // top-1 accuracy is the accuracy
+        assert_eq!(evaluation.get("Top-1 Accuracy"), evaluation.get("Accuracy"));
+        // all 3 classes are always in the top 3
+        assert_eq!(evaluation.get("Top-3 Accuracy"), Some(1.0));
+    }
+
+    // Custom layer multiplying its inputs by a learnable factor per feature
+    struct Scale {
+        input: Tensor,
+        factors: Tensor,
+        factors_grad: Tensor,
+        output_shape: Vec<usize>,
+    }
+
+    impl Scale {
+        fn new() -> Scale {
+            Scale {
+                input: Tensor::new(vec![], vec![]),
+                factors: Tensor::new(vec![], vec![]),
+                factors_grad: Tensor::new(vec![], vec![]),
+                output_shape: vec![],
+            }
+        }
+
+        fn scale(&self, tensor: &Tensor) -> Tensor {
+            let features = self.factors.data.len();
+            let data = tensor.data.iter().enumerate().map(|(i, x)| x * self.factors.data[i % features]).collect();
+            Tensor::new(data, tensor.shape.to_vec())
+        }
+    }
+
+    impl Layer for Scale {
+        fn forward(&mut self, input: Tensor, _training: bool) -> Tensor {
+            self.input = input;
+            self.scale(&self.input)
+        }
+
+        fn backward(&mut self, gradient: &Tensor) -> Tensor {
+            self.factors_grad = gradient.mult_el(&self.input).get_sum(0);
+            self.scale(gradient)
+        }
+
+        fn get_info(&self) -> LayerInfo {
+            LayerInfo {
+                layer_type: "Scale".to_string(),
+                output_shape: self.output_shape.to_vec(),
+                trainable_param: self.factors.data.len(),
+                non_trainable_param: 0,
+            }
+        }
+
+        fn build(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, String> {
+            if input_shape.len() != 1 {
+                return Err("expected a 1D input shape".to_string());
+            }
+            self.factors = Tensor::one(vec![1, input_shape[0]]);
+            self.output_shape = input_shape.to_vec();
+            Ok(self.output_shape.to_vec())
+        }
+
+        fn get_params_list(&self) -> Vec<LearnableParams> {
+            vec![LearnableParams::Weights]
+        }
+
+        fn get_param(&mut self, param: &LearnableParams) -> &mut Tensor {
+            match param {
+                LearnableParams::Weights => &mut self.factors,
+                _ => panic!("Scale does not have {:?} parameters.", param),
+            }
+        }
+
+        fn get_grad(&self, param: &LearnableParams) -> &Tensor {
+            match param {
+                LearnableParams::Weights => &self.factors_grad,
+                _ => panic!("Scale does not have {:?} parameters.", param),
+            }
+        }
+    }
+
+    #[test]
+    fn test_freeze_layers() {
+        let data = (0..32).map(|i| {
+            let x = i as f64 / 32.0;
+            vec![x, 1.0 - x, x * x]
+        }).collect();
+        let dataset = Dataset::from_raw_data(data).unwrap();
+
+        let mut model = Sequential::new();
+        model.add(LayerEnum::dense(Some(2), 4));
+        model.add(ReLU);
+        model.add(LayerEnum::dense(None, 1));
+        model.compile(MSE{}, SGD::new(0.01), vec![]).unwrap();
+
+        // copy "pretrained" weights in the first layer and only train the head
+        let pretrained = Tensor::new(vec![0.5, -0.5, 1.0, 0.2, -0.3, 0.8, 0.1, 0.4], vec![2, 4]);
+        *model.layers[0].get_param(&LearnableParams::Weights) = pretrained.clone();
+        model.set_trainable(0, false);
+        assert!(!model.layers[0].is_trainable() && model.layers[2].is_trainable());
+        let head = model.layers[2].get_param(&LearnableParams::Weights).clone();
+
+        model.fit(&dataset, 5, false);
+        assert_eq!(model.layers[0].get_param(&LearnableParams::Weights), &pretrained);
+        assert_eq!(model.layers[0].get_param(&LearnableParams::Biases), &Tensor::zero(vec![1, 4]));
+        assert_ne!(model.layers[2].get_param(&LearnableParams::Weights), &head);
+
+        // the flag is kept when the model is compiled again
+        model.compile(MSE{}, SGD::new(0.01), vec![]).unwrap();
+        assert!(!model.layers[0].is_trainable());
+
+        // fine-tune the whole network
+        model.set_trainable(0, true);
+        let weights = model.layers[0].get_param(&LearnableParams::Weights).clone();
+        model.fit(&dataset, 1, false);
+        assert_ne!(model.layers[0].get_param(&LearnableParams::Weights), &weights);
+
+        // custom layers with parameters must implement `set_trainable` to be frozen
+        let mut model = Sequential::new();
+        model.set_input_shape(vec![2]);
+        model.add_layer(Box::new(Scale::new()));
+        model.add(ReLU);
+        model.set_trainable(1, false);
+        model.set_trainable(0, false);
+        assert_eq!(model.compile(MSE{}, SGD::new(0.01), vec![]),
+            Err(ModelError::NotFreezable { layer: 0, layer_type: "Scale".to_string() }));
+    }
+
+    #[test]
+    fn test_custom_layer() {
+        let mut data = Vec::new();
+        for i in 0..60 {
+            let x = (i % 10) as f64 / 10.0;
+            data.push(vec![x, 1.0 - x, (x > 0.45) as usize as f64]);
+        }
+        let mut dataset = Dataset::from_raw_data(data).unwrap();
+        dataset.one_hot_encode(2);
+
+        let mut model = Sequential::new();
+        model.set_input_shape(vec![2]);
+        model.add_layer(Box::new(Scale::new()));
+        model.add(LayerEnum::dense(None, 4));
+        model.add(Custom(Box::new(|_seed| Box::new(Scale::new()))));
+        model.add(LayerEnum::dense(None, 2));
+        model.compile(CategoricalEntropy{},
+            SGD::new(0.1),
+            vec![]).unwrap();
+
+        assert_eq!(model.layers[0].get_info().trainable_param, 2);
+        let loss_before = model.evaluate(&dataset, RowType::Train).unwrap().loss;
+        model.fit(&dataset, 30, false);
+        assert!(model.evaluate(&dataset, RowType::Train).unwrap().loss < loss_before);
+
+        let info = model.layers[2].get_info();
+        assert_eq!((info.layer_type, info.output_shape, info.trainable_param), ("Scale".to_string(), vec![4], 4));
+        assert_ne!(model.layers[0].get_grad(&LearnableParams::Weights).data, vec![0.0, 0.0]);
+
+        // the layer instance is built again when the model is compiled again
+        model.compile(CategoricalEntropy{}, SGD::new(0.1), vec![]).unwrap();
+        assert_eq!(model.layers[0].get_info().layer_type, "Scale");
+        assert_eq!(model.layers[0].get_param(&LearnableParams::Weights), &Tensor::one(vec![1, 2]));
+        model.fit(&dataset, 1, false);
+
+        // a custom layer with an incompatible input shape is reported by compile
+        let mut model = Sequential::new();
+        model.set_input_shape(vec![2, 3]);
+        model.add_layer(Box::new(Scale::new()));
+        let error = model.compile(CategoricalEntropy{}, SGD::new(0.1), vec![]).err();
+        assert_eq!(error, Some(ModelError::IncompatibleShape {
+            layer: 0,
+            layer_type: "Scale".to_string(),
+            input_shape: vec![2, 3],
+            message: "expected a 1D input shape".to_string(),
+        }));
+        model.set_input_shape(vec![3]);
+        model.compile(CategoricalEntropy{}, SGD::new(0.1), vec![]).unwrap();
+        assert_eq!(model.layers[0].get_info().trainable_param, 3);
+    }
+
+    // Identity layer recording the first feature of the rows it is trained on
+    struct Record {
+        rows: Rc<RefCell<Vec<f64>>>,
+        output_shape: Vec<usize>,
+    }
+
+    impl Layer for Record {
+        fn forward(&mut self, input: Tensor, training: bool) -> Tensor {
+            if training {
+                let features = input.shape[1];
+                self.rows.borrow_mut().extend(input.data.iter().step_by(features));
+            }
+            input
+        }
+
+        fn backward(&mut self, gradient: &Tensor) -> Tensor {
+            gradient.clone()
+        }
+
+        fn get_info(&self) -> LayerInfo {
+            LayerInfo {
+                layer_type: "Record".to_string(),
+                output_shape: self.output_shape.to_vec(),
+                trainable_param: 0,
+                non_trainable_param: 0,
+            }
+        }
+
+        fn build(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, String> {
+            self.output_shape = input_shape.to_vec();
+            Ok(self.output_shape.to_vec())
+        }
+
+        fn get_params_list(&self) -> Vec<LearnableParams> {
+            vec![]
+        }
+
+        fn get_param(&mut self, _param: &LearnableParams) -> &mut Tensor {
+            panic!("Record does not have learnable parameters.")
+        }
+
+        fn get_grad(&self, _param: &LearnableParams) -> &Tensor {
+            panic!("Record does not have learnable parameters.")
+        }
+    }
+
+    #[test]
+    fn test_shuffle_batches() {
+        let data = (0..20).map(|i| vec![i as f64, 0.0]).collect();
+        let dataset = Dataset::from_raw_data(data).unwrap();
+
+        // order of the training rows over the epochs of each call to `fit`
+        let batch_order = |shuffle: bool, seed: u32, fits: &[u32]| {
+            let rows = Rc::new(RefCell::new(vec![]));
+            let mut model = Sequential::new();
+            model.set_seed(seed);
+            model.set_shuffle(shuffle);
+            model.set_input_shape(vec![1]);
+            model.add_layer(Box::new(Record { rows: rows.clone(), output_shape: vec![] }));
+            model.add(LayerEnum::dense(None, 1));
+            model.compile(MSE{}, SGD::new(0.001), vec![]).unwrap();
+            for epochs in fits {
+                model.fit(&dataset, *epochs, false);
+            }
+            let rows = rows.borrow().clone();
+            rows
+        };
+
+        let ordered: Vec<f64> = (0..20).map(|i| i as f64).collect();
+        assert_eq!(batch_order(false, 1, &[2]), [ordered.clone(), ordered.clone()].concat());
+
+        let shuffled = batch_order(true, 1, &[2]);
+        assert_eq!(shuffled, batch_order(true, 1, &[2]));
+        assert_ne!(shuffled, batch_order(true, 2, &[2]));
+        // the epochs of successive calls to `fit` follow each other
+        assert_eq!(shuffled, batch_order(true, 1, &[1, 1]));
+        // every row once per epoch, in another order at each epoch
+        let (first, second) = shuffled.split_at(20);
+        assert_ne!(first, second);
+        let mut sorted = first.to_vec();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(sorted, ordered);
+        assert_ne!(first, &ordered[..]);
+    }
 }
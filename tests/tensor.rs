@@ -147,8 +147,78 @@ mod tensor_tests {
 
     #[test]
     fn test_mask() {
-         let mask = Tensor::mask(&vec![10, 10], 0.4, 777);
-         assert_eq!(mask.data.iter().sum::<f64>() as usize, 100);
+        let mask = Tensor::mask(&[100, 100], 0.4, 777);
+        // values are sampled: about 40% are dropped and the others scaled
+        let dropped = mask.data.iter().filter(|x| **x == 0.0).count();
+        assert!((dropped as f64 / 10000.0 - 0.4).abs() < 0.02);
+        assert!(mask.data.iter().all(|x| *x == 0.0 || (x - 1.0 / 0.6).abs() < 1e-12));
+        assert!((mask.data.iter().sum::<f64>() / 10000.0 - 1.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_random_distributions() {
+        let mean = |t: &Tensor| t.data.iter().sum::<f64>() / t.data.len() as f64;
+
+        let random = Tensor::random(vec![100, 100], 1);
+        assert!(random.data.iter().all(|x| x.abs() <= 1.0));
+        assert!(random.data.iter().any(|x| *x < -0.9) && random.data.iter().any(|x| *x > 0.9));
+        assert!(mean(&random).abs() < 0.02);
+
+        let uniform = Tensor::random_uniform(vec![10000], 2.0, 3.0, 2);
+        assert!(uniform.data.iter().all(|x| (2.0..3.0).contains(x)));
+        assert_eq!(uniform, Tensor::random_uniform(vec![10000], 2.0, 3.0, 2));
+
+        let normal = Tensor::random_normal(vec![10000], 1.0, 2.0, 3);
+        let variance = normal.data.iter().map(|x| (x - 1.0).powi(2)).sum::<f64>() / 10000.0;
+        assert!((mean(&normal) - 1.0).abs() < 0.05 && (variance.sqrt() - 2.0).abs() < 0.05);
+
+        let truncated = Tensor::truncated_normal(vec![10000], 1.0, 2.0, 4);
+        assert!(truncated.data.iter().all(|x| (x - 1.0).abs() <= 4.0));
+
+        let bernoulli = Tensor::bernoulli(vec![10000], 0.3, 5);
+        assert!(bernoulli.data.iter().all(|x| *x == 0.0 || *x == 1.0));
+        assert!((mean(&bernoulli) - 0.3).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_categorical_and_multinomial() {
+        let probs = Tensor::new(vec![
+            0.0, 1.0, 3.0,
+            1.0, 0.0, 0.0], vec![2, 3]);
+
+        let samples = Tensor::categorical(&probs, 1000, 6);
+        assert_eq!(samples.shape, vec![2, 1000]);
+        let (first, second) = samples.data.split_at(1000);
+        // class 0 is never drawn for the first row, class 2 three times more than class 1
+        assert!(first.iter().all(|c| *c == 1.0 || *c == 2.0));
+        let twos = first.iter().filter(|c| **c == 2.0).count();
+        assert!((twos as f64 / 1000.0 - 0.75).abs() < 0.05);
+        assert!(second.iter().all(|c| *c == 0.0));
+
+        let counts = Tensor::multinomial(&probs, 50, 7);
+        assert_eq!(counts.shape, vec![2, 3]);
+        assert_eq!(counts.data[0], 0.0);
+        assert_eq!(counts.data[0] + counts.data[1] + counts.data[2], 50.0);
+        assert_eq!(&counts.data[3..], &[50.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_permutation_and_choice() {
+        let mut permutation = Tensor::permutation(10, 8).data;
+        assert_ne!(permutation, (0..10).map(|i| i as f64).collect::<Vec<f64>>());
+        permutation.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(permutation, (0..10).map(|i| i as f64).collect::<Vec<f64>>());
+
+        // without replacement all indices are distinct
+        let mut choice = Tensor::choice(20, 15, false, 9).data;
+        choice.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        choice.dedup();
+        assert_eq!(choice.len(), 15);
+
+        // with replacement more indices than values can be drawn
+        let choice = Tensor::choice(3, 100, true, 10);
+        assert_eq!(choice.shape, vec![100]);
+        assert!(choice.data.iter().all(|i| (0.0..3.0).contains(i)));
     }
 
     #[test]
@@ -246,4 +316,21 @@ mod tensor_tests {
         assert_eq!(a_t, result);
     }
 
+    #[test]
+    fn test_get_permuted() {
+        // [1, 2, 2, 3] tensor in NHWC
+        let a = Tensor::new(vec![
+            1.0, 2.0, 3.0,   4.0, 5.0, 6.0,
+            7.0, 8.0, 9.0,   10.0, 11.0, 12.0], vec![1, 2, 2, 3]);
+
+        // NHWC -> NCHW
+        let result = Tensor::new(vec![
+            1.0, 4.0, 7.0, 10.0,
+            2.0, 5.0, 8.0, 11.0,
+            3.0, 6.0, 9.0, 12.0], vec![1, 3, 2, 2]);
+
+        assert_eq!(a.get_permuted(&[0, 3, 1, 2]), result);
+        assert_eq!(result.get_permuted(&[0, 2, 3, 1]), a);
+    }
+
 }